@@ -1,7 +1,7 @@
 use std::fs::{File, OpenOptions};
-use std::io::{Error, ErrorKind, Write, BufRead, BufReader};
+use std::io::{Error, Write, BufRead, BufReader};
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use rand::Rng;
@@ -10,7 +10,7 @@ use rand::distributions::{Distribution, Uniform};
 use rayon::prelude::*;
 
 use crate::ds::*;
-use crate::solve::{State, SData, AData, Cache};
+use crate::solve::{State, SData, AData, Cache, FbTable};
 
 // TODO default settings to out's settings if existed
 
@@ -25,6 +25,7 @@ pub struct GGen {
   pub ntops1: Range<u32>,
   pub ntops2: Range<u32>,
   pub ecuts: Range<u32>,
+  pub hard: bool,
   pub niter: usize,
 }
 
@@ -61,11 +62,8 @@ impl GGen {
 
       // check first lines of metadata
       for meta_line in &meta {
-        if &lines.next().ok_or(Error::new(ErrorKind::Other,"not enough lines!"))?? != meta_line {
-          return Err(Error::new(
-            ErrorKind::Other,
-            "metadata does not match!"
-          ));
+        if &lines.next().ok_or(Error::other("not enough lines!"))?? != meta_line {
+          return Err(Error::other("metadata does not match!"));
         }
       }
     } else {
@@ -81,6 +79,8 @@ impl GGen {
     // generate data in parallel
     let f = Mutex::new(self.open_file(out)?);
     let i = Mutex::new(1);
+    let fbt = Arc::new(FbTable::from_banks(&self.gwb, &self.awb));
+    fbt.fill();
     (0..self.niter).into_par_iter().for_each(|_| {
       // take samples
       let mut rng = rand::thread_rng();
@@ -89,11 +89,11 @@ impl GGen {
       let ntops1 = self.ntops1.sample(&mut rng);
       let ntops2 = self.ntops2.sample(&mut rng);
       let ecut = self.ecuts.sample(&mut rng);
-      let hard = false; // FOR NOW ALWAYS EASY BC CACHE DOESNT CHECK GWS
+      let hard = self.hard;
 
       // make state
       let aws2 = self.awb.pick(&mut rng, alen as usize);
-      let s = State::new2(self.gwb.data.clone(), aws2, self.wlen, turns as u32, false);
+      let s = State::from_table(fbt.clone(), &aws2, turns as u32, hard);
       let mut sd = SData::new(self.adata.clone(), self.cache.clone(),
                               ntops1 as u32, ntops2 as u32, ecut as u32);
 
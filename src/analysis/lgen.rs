@@ -1,7 +1,7 @@
 use std::fs::{File, OpenOptions};
-use std::io::{Error, ErrorKind, Write, BufRead, BufReader};
+use std::io::{Error, Write, BufRead, BufReader};
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::collections::HashMap;
 
@@ -11,7 +11,7 @@ use rand::distributions::{Distribution, Uniform};
 use rayon::prelude::*;
 
 use crate::ds::*;
-use crate::solve::{State, SData, AData, Cache};
+use crate::solve::{State, SData, AData, Cache, FbTable};
 
 // TODO default settings to out's settings if existed
 
@@ -67,11 +67,8 @@ impl LGen {
       
       // check first lines of metadata
       for meta_line in &meta {
-        if &lines.next().ok_or(Error::new(ErrorKind::Other,"not enough lines!"))?? != meta_line {
-          return Err(Error::new(
-            ErrorKind::Other,
-            "metadata does not match!"
-          ));
+        if &lines.next().ok_or(Error::other("not enough lines!"))?? != meta_line {
+          return Err(Error::other("metadata does not match!"));
         }
       }
 
@@ -100,6 +97,8 @@ impl LGen {
     let f = Mutex::new(f);
     let lbs = Mutex::new(lbs);
     let i = Mutex::new(1);
+    let fbt = Arc::new(FbTable::from_banks(&self.gwb, &self.awb));
+    fbt.fill();
 
     let alens: Vec<usize> = (self.alens.a..=self.alens.b).step_by(self.step).collect();
     alens.into_par_iter().for_each(|alen| {
@@ -109,7 +108,7 @@ impl LGen {
       for _ in 0..self.niter {
         // make state
         let aws2 = self.awb.pick(&mut rng, alen as usize);
-        let s = State::new2(self.gwb.data.clone(), aws2, self.wlen, self.turns as u32, false);
+        let s = State::from_table(fbt.clone(), &aws2, self.turns, false);
         let mut sd = SData::new(self.adata.clone(), self.cache.clone(),
                                 self.ntops1 as u32, self.ntops2, self.ecut as u32);
 
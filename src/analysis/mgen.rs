@@ -0,0 +1,135 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Error, Write, BufRead, BufReader};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rayon::prelude::*;
+
+use crate::ds::*;
+use crate::solve::{MState, MData, MCache, AData, FbTable};
+
+/// generates multi-board heuristic data: each of a random position's top words
+/// by heuristic, with their evaluations
+pub struct MGen {
+  pub gwb: WBank,
+  pub awb: WBank,
+  pub wlen: u32,
+  pub adata: AData,
+  pub boards: Range<u32>,
+  pub alens: Range<usize>,
+  pub extras: Range<u32>,
+  pub unshared: Range<f64>,
+  pub risk: Range<f64>,
+  pub ntops: u32,
+  pub nsamples: u32,
+  pub hard: bool,
+  pub niter: usize,
+}
+
+impl MGen {
+  fn header() -> &'static str {
+    "boards,size,turns,mode,unshared,risk,rank,heur,eval"
+  }
+
+  fn metadata(&self) -> Vec<String> {
+    vec![
+      "# kind: mgen".to_owned(),
+      format!("# boards: {}", self.boards),
+      format!("# alens: {}", self.alens),
+      format!("# extras: {}", self.extras),
+      format!("# unshared: {}", self.unshared),
+      format!("# risk: {}", self.risk),
+      format!("# ntops: {}", self.ntops),
+      format!("# nsamples: {}", self.nsamples),
+      format!("# hard: {}", self.hard),
+    ]
+  }
+
+  // open, check formatting, append if existing
+  fn open_file(&self, out: &Path) -> Result<File, Error> {
+    let existed = out.exists();
+    let meta = self.metadata();
+    let mut f = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(out)?;
+
+    if existed {
+      // check metadata
+      let f = File::open(out)?;
+      let reader = BufReader::new(f);
+      let mut lines = reader.lines();
+
+      // check first lines of metadata
+      for meta_line in &meta {
+        if &lines.next().ok_or(Error::other("not enough lines!"))?? != meta_line {
+          return Err(Error::other("metadata does not match!"));
+        }
+      }
+    } else {
+      // write metadata + header if new
+      writeln!(f, "{}", meta.join("\n"));
+      writeln!(f, "{}", Self::header());
+    }
+
+    Ok(f)
+  }
+
+  pub fn run(&mut self, out: &Path) -> Result<(), Error> {
+    // generate data in parallel
+    let f = Mutex::new(self.open_file(out)?);
+    let i = Mutex::new(1);
+    let fbt = Arc::new(FbTable::from_banks(&self.gwb, &self.awb));
+    fbt.fill();
+    (0..self.niter).into_par_iter().for_each(|_| {
+      // take samples
+      let mut rng = rand::thread_rng();
+      let boards = self.boards.sample(&mut rng);
+      let turns = boards + self.extras.sample(&mut rng);
+      let awss: Vec<Vec<Word>> = (0..boards)
+        .map(|_| {
+          let alen = self.alens.sample(&mut rng);
+          self.awb.pick(&mut rng, alen)
+        })
+        .collect();
+      let mut md = MData::new(self.adata.clone(), MCache::new(64, 16),
+                              self.ntops, self.nsamples, 15);
+      md.unshared = self.unshared.sample(&mut rng);
+      md.risk = self.risk.sample(&mut rng);
+
+      // evaluate top words in heuristic order
+      let s = MState::from_table(fbt.clone(), &awss, turns, self.hard);
+      let rows: Vec<String> = s.top_words(&md)
+        .iter()
+        .enumerate()
+        .map(|(rank, gw)| {
+          let heur = s.heuristic(gw, &md);
+          let eval = s.solve_given(*gw, &mut md).map_or(f64::INFINITY, |mt| mt.get_eval());
+          format!(
+            "{},{},{},{},{:.3},{:.3},{},{:.3},{:.3}",
+            boards,
+            s.size(),
+            turns,
+            if self.hard { "H" } else { "E" },
+            md.unshared,
+            md.risk,
+            rank + 1,
+            heur,
+            eval,
+          )
+        })
+        .collect();
+
+      // print and write results to file
+      let mut i = i.lock().unwrap();
+      let mut f = f.lock().unwrap();
+      for s in rows {
+        println!("{}. {}", *i, s);
+        writeln!(f, "{}", s);
+      }
+      *i += 1;
+    });
+
+    Ok(())
+  }
+}
@@ -2,3 +2,5 @@ pub mod ggen;
 pub use ggen::GGen;
 pub mod lgen;
 pub use lgen::LGen;
+pub mod mgen;
+pub use mgen::MGen;
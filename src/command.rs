@@ -25,7 +25,7 @@ pub enum Commands {
     /// list potential answers
     #[clap(long)]
     alist: bool,
-    /// output decision tree to file
+    /// output decision tree to file (json if it ends in .json, otherwise text)
     #[clap(long)]
     dt: Option<String>,
     /// word length
@@ -55,6 +55,107 @@ pub enum Commands {
     /// the maximum number of answer words left for an "endgame"
     #[clap(long, default_value_t=15)]
     ecut: u32,
+    /// try every guess, proving the solution optimal
+    #[clap(long)]
+    exact: bool,
+    /// also prune with the lower bounds data (faster, but the bounds are unproven, so totals
+    /// can be worse than without it; compare against a run without it to check)
+    #[clap(long)]
+    lbprune: bool,
+    /// what to minimize, comma separated in order of priority (tot, max, win<k>, n<k>)
+    #[clap(long, default_value="tot")]
+    objective: String,
+    /// stop after this many seconds, widening ntops2 up to its value
+    #[clap(long)]
+    time_limit: Option<f64>,
+    /// book path (defaults to the user cache directory)
+    #[clap(long)]
+    book: Option<String>,
+    /// don't read or write the book
+    #[clap(long)]
+    nobook: bool,
+    /// add the solution to the book
+    #[clap(long)]
+    savebook: bool,
+    /// show progress of the root's candidates on stderr
+    #[clap(long)]
+    progress: bool,
+    /// print search statistics
+    #[clap(long)]
+    stats: bool,
+    /// play against an adversary giving the feedback that keeps the most answers, as in absurdle
+    #[clap(long)]
+    adversarial: bool,
+    /// allow guesses that aren't in the word bank (or break hard mode)
+    #[clap(long)]
+    dirty: bool,
+    /// output format (text, json)
+    #[clap(long, default_value="text")]
+    format: String,
+    /// play the given tree's guesses instead of solving (any tree format)
+    #[clap(long)]
+    tree: Option<String>,
+  },
+  /// solve multi-board game state
+  #[cfg(feature = "solve")]
+  Msolve {
+    /// the game state to solve from, with comma separated feedbacks for each board
+    #[clap(value_parser, default_value="")]
+    gamestate: String,
+    /// the number of boards
+    #[clap(long, default_value_t=4)]
+    boards: u32,
+    /// play in hard mode
+    #[clap(long)]
+    hard: bool,
+    /// which board's hints hard mode guesses must be consistent with (any, first)
+    #[clap(long, default_value="any")]
+    hrule: String,
+    /// list top word evaluations
+    #[clap(long)]
+    elist: bool,
+    /// word length
+    #[clap(long, default_value_t=5)]
+    wlen: u8,
+    /// word bank path
+    #[clap(long, default_value_t=String::from(DEFWBP))]
+    wbp: String,
+    /// heuristic data path
+    #[clap(long, default_value_t=String::from(DEFHDP))]
+    hdp: String,
+    /// lower bounds data path
+    #[clap(long, default_value_t=String::from(DEFLDP))]
+    ldp: String,
+    /// the number of top heuristic words to try
+    #[clap(long, default_value_t=5)]
+    ntops: u32,
+    /// the number of answer samples to try for each guess
+    #[clap(long, default_value_t=5)]
+    nsamples: u32,
+    /// try every answer combination when there are at most this many
+    #[clap(long, default_value_t=0)]
+    exhaust: usize,
+    /// the seed for sampling answers
+    #[clap(long, default_value_t=0)]
+    seed: u64,
+    /// the maximum number of turns to solve in (defaults to the number of boards + 5)
+    #[clap(long)]
+    turns: Option<u32>,
+    /// allow guesses that aren't in the word bank (or break hard mode)
+    #[clap(long)]
+    dirty: bool,
+  },
+  /// manage the book of solved gamestates
+  #[cfg(feature = "solve")]
+  Book {
+    #[clap(subcommand)]
+    command: BookCommands,
+  },
+  /// load and query decision tree files
+  #[cfg(feature = "solve")]
+  Tree {
+    #[clap(subcommand)]
+    command: TreeCommands,
   },
   /// generate heuristic data
   #[cfg(feature = "gen")]
@@ -86,6 +187,9 @@ pub enum Commands {
     /// the maximum number of turns to solve in
     #[clap(long, default_value_t=6)]
     turns: u32,
+    /// play in hard mode
+    #[clap(long)]
+    hard: bool,
     /// endgame cutoff
     #[clap(long, default_value_t=15)]
     ecut: u32,
@@ -123,6 +227,9 @@ pub enum Commands {
     /// the range of maximum numbers of turns to solve in
     #[clap(long, default_value_t=Range::new(1, 6, true))]
     turns: Range<u32>,
+    /// play in hard mode
+    #[clap(long)]
+    hard: bool,
     /// endgame cutoff
     #[clap(long, default_value_t=Range::new(1, 30, true))]
     ecut: Range<u32>,
@@ -167,6 +274,188 @@ pub enum Commands {
     #[clap(long, default_value_t=15)]
     ecut: u32,
   },
+  /// generate multi-board heuristic data
+  #[cfg(feature = "gen")]
+  Mgen {
+    /// the number of data points to generate
+    #[clap(value_parser)]
+    niter: usize,
+    /// the file to output data to
+    #[clap(value_parser)]
+    out: String,
+    /// word length
+    #[clap(long, default_value_t = 5)]
+    wlen: u8,
+    /// word bank path
+    #[clap(long, default_value_t=String::from(DEFWBP))]
+    wbp: String,
+    /// heuristic data path
+    #[clap(long, default_value_t=String::from(DEFHDP))]
+    hdp: String,
+    /// lower bounds data path
+    #[clap(long, default_value_t=String::from(DEFLDP))]
+    ldp: String,
+    /// the range of numbers of boards
+    #[clap(long, default_value_t=Range::new(2, 8, true))]
+    boards: Range<u32>,
+    /// the range of each board's answer lengths
+    #[clap(long, default_value_t=Range::new(1, 20, true))]
+    alens: Range<usize>,
+    /// the range of turns beyond one per board
+    #[clap(long, default_value_t=Range::new(0, 5, true))]
+    extras: Range<u32>,
+    /// the range of heuristic fractions of unshared extra guesses
+    #[clap(long, default_value_t=Range::new(0., 1., true))]
+    unshared: Range<f64>,
+    /// the range of heuristic penalties for going over the turns left
+    #[clap(long, default_value_t=Range::new(0., 2., true))]
+    risk: Range<f64>,
+    /// the number of top heuristic words to evaluate
+    #[clap(long, default_value_t=5)]
+    ntops: u32,
+    /// the number of answer samples to try for each guess
+    #[clap(long, default_value_t=5)]
+    nsamples: u32,
+    /// play in hard mode
+    #[clap(long)]
+    hard: bool,
+  },
+}
+
+#[cfg(feature = "solve")]
+#[derive(Subcommand)]
+pub enum BookCommands {
+  /// solve game states and add them to the book
+  Build {
+    /// the game states to solve from
+    #[clap(value_parser, required=true)]
+    gamestates: Vec<String>,
+    /// book path (defaults to the user cache directory)
+    #[clap(long)]
+    book: Option<String>,
+    /// word length
+    #[clap(long, default_value_t=5)]
+    wlen: u8,
+    /// word bank path
+    #[clap(long, default_value_t=String::from(DEFWBP))]
+    wbp: String,
+    /// heuristic data path
+    #[clap(long, default_value_t=String::from(DEFHDP))]
+    hdp: String,
+    /// lower bounds data path
+    #[clap(long, default_value_t=String::from(DEFLDP))]
+    ldp: String,
+    /// play in hard mode
+    #[clap(long)]
+    hard: bool,
+    /// the number of top soft heuristic words to try
+    #[clap(long, default_value_t=1000)]
+    ntops1: u32,
+    /// the number of top hard heuristic words to try
+    #[clap(long, default_value_t=10)]
+    ntops2: u32,
+    /// the maximum number of turns to solve in
+    #[clap(long, default_value_t=6)]
+    turns: u32,
+    /// the maximum number of answer words left for an "endgame"
+    #[clap(long, default_value_t=15)]
+    ecut: u32,
+    /// try every guess, proving the solution optimal
+    #[clap(long)]
+    exact: bool,
+    /// what to minimize, comma separated in order of priority (tot, max, win<k>, n<k>)
+    #[clap(long, default_value="tot")]
+    objective: String,
+  },
+  /// list the book's entries
+  List {
+    /// book path (defaults to the user cache directory)
+    #[clap(long)]
+    book: Option<String>,
+  },
+  /// remove entries from the book (defaults to all)
+  Clear {
+    /// the game states to remove
+    #[clap(value_parser)]
+    gamestates: Vec<String>,
+    /// book path (defaults to the user cache directory)
+    #[clap(long)]
+    book: Option<String>,
+  },
+}
+
+#[cfg(feature = "solve")]
+#[derive(Subcommand)]
+pub enum TreeCommands {
+  /// follow a game state down a tree, showing its next guess
+  Walk {
+    /// the tree file (text, json or selby)
+    #[clap(value_parser)]
+    tree: String,
+    /// the game state to follow
+    #[clap(value_parser, default_value="")]
+    gamestate: String,
+    /// list potential answers
+    #[clap(long)]
+    alist: bool,
+    /// output the subtree to file
+    #[clap(long)]
+    dt: Option<String>,
+  },
+  /// check a tree solves every answer in a word bank
+  Verify {
+    /// the tree file (text, json or selby)
+    #[clap(value_parser)]
+    tree: String,
+    /// word length
+    #[clap(long, default_value_t=5)]
+    wlen: u8,
+    /// word bank path
+    #[clap(long, default_value_t=String::from(DEFWBP))]
+    wbp: String,
+    /// the maximum number of turns to solve in
+    #[clap(long, default_value_t=6)]
+    turns: u32,
+    /// check the tree plays in hard mode
+    #[clap(long)]
+    hard: bool,
+  },
+  /// summarize a tree
+  Stats {
+    /// the tree file (text, json or selby)
+    #[clap(value_parser)]
+    tree: String,
+  },
+  /// show the guesses a tree plays for answers
+  Trace {
+    /// the tree file (text, json or selby)
+    #[clap(value_parser)]
+    tree: String,
+    /// the answers to trace (defaults to every answer in the word bank)
+    #[clap(value_parser)]
+    answers: Vec<String>,
+    /// word length
+    #[clap(long, default_value_t=5)]
+    wlen: u8,
+    /// word bank path
+    #[clap(long, default_value_t=String::from(DEFWBP))]
+    wbp: String,
+    /// the number of answers taking the most turns to list when tracing the word bank
+    #[clap(long, default_value_t=10)]
+    top: usize,
+  },
+  /// convert a tree file to another format
+  Convert {
+    /// the tree file (text, json or selby)
+    #[clap(value_parser)]
+    tree: String,
+    /// the file to output the tree to
+    #[clap(value_parser)]
+    out: String,
+    /// output format (text, json, selby; defaults to json if out ends in .json, otherwise text)
+    #[clap(long)]
+    format: Option<String>,
+  },
 }
 
 pub fn cli_parse() -> Cli {
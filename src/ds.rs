@@ -1,6 +1,7 @@
 use core::str::FromStr;
+use std::cmp::Reverse;
 use std::fmt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Write, Error, ErrorKind};
 use std::path::Path;
@@ -11,16 +12,24 @@ use rand::distributions::uniform::{Uniform, SampleUniform};
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use serde_json::{json, Map, Value};
+
 pub const NLETS: usize = 5;
 pub const NGUESSES: usize = 6;
 pub const NEXTRA: usize = 5;
 pub const NWORDS: usize = 2309;
 pub const MINWLEN: usize = 4;
-pub const MAXWLEN: usize = 11;
+// feedbacks are u16 bitsets
+pub const MAXWLEN: usize = 16;
 
 pub const DEFWBP: &'static str = "/usr/share/hustle/bank1.csv";
 pub const DEFHDP: &'static str = "/usr/share/hustle/happrox.csv";
 pub const DEFLDP: &'static str = "/usr/share/hustle/lbounds.csv";
+// version of the tree file formats
+pub const DTREE_VERSION: u32 = 1;
+// weights with fractions or summing to more are scaled to sum to about this,
+// so weighted totals fit in a u32
+pub const MAXWSUM: u32 = 1 << 20;
 
 pub fn is_alpha(c: char) -> bool {
   ('a'..='z').contains(&c) || ('A'..='Z').contains(&c)
@@ -45,7 +54,7 @@ impl Word {
   pub fn from(s: String) -> Option<Self> {
     let wlen = s.len() as u8;
     let mut data = [0u8; MAXWLEN];
-    if s.len() > MAXWLEN {
+    if s.len() > MAXWLEN || !s.chars().all(is_alpha) {
       return None;
     }
     for (i, c) in s.to_ascii_uppercase().chars().enumerate() {
@@ -57,7 +66,7 @@ impl Word {
   pub fn from_str(s: &str) -> Option<Self> {
     let wlen = s.len() as u8;
     let mut data = [0; MAXWLEN];
-    if s.len() > MAXWLEN {
+    if s.len() > MAXWLEN || !s.chars().all(is_alpha) {
       return None;
     }
     for (i, c) in s.to_ascii_uppercase().chars().enumerate() {
@@ -209,28 +218,16 @@ impl Feedback {
   }
 
   pub fn from_str(s: &str) -> Option<Self> {
-    let wlen = s.len() as u8;
-    if wlen > MAXWLEN as u8 {
-      return None;
-    }
-    let mut fb = Feedback {
-      g_bs: 0,
+    PFeedback::from_str(s)?.known()
+  }
+
+  /// all greens
+  pub fn correct(wlen: u8) -> Self {
+    Feedback {
+      g_bs: ((1u32 << wlen) - 1) as u16,
       y_bs: 0,
       wlen,
-    };
-    for (i, c) in s
-      .to_ascii_uppercase()
-      .chars()
-      .take(wlen as usize)
-      .enumerate()
-    {
-      if c == 'G' {
-        fb.g_bs |= 1 << i;
-      } else if c == 'Y' {
-        fb.y_bs |= 1 << i;
-      }
     }
-    Some(fb)
   }
 
   pub fn get_g(&self, i: u8) -> bool {
@@ -242,10 +239,25 @@ impl Feedback {
   }
 
   pub fn is_correct(&self) -> bool {
-    self.g_bs == ((1 << self.wlen) - 1)
+    self.g_bs as u32 == (1u32 << self.wlen) - 1
   }
 }
 
+/// the feedback an absurdle-like adversary gives, from the number of answers each
+/// feedback keeps: the most answers, then the fewest greens, then the fewest yellows
+pub fn adversary_fb<I>(counts: I) -> Option<Feedback>
+where
+  I: IntoIterator<Item = (Feedback, usize)>, {
+  counts.into_iter()
+    .max_by_key(|(fb, ct)| (
+      *ct,
+      Reverse(fb.g_bs.count_ones()),
+      Reverse(fb.y_bs.count_ones()),
+      Reverse(fb.to_id()),
+    ))
+    .map(|(fb, _)| fb)
+}
+
 impl fmt::Display for Feedback {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "{}", self.to_string())
@@ -258,13 +270,98 @@ impl fmt::Debug for Feedback {
   }
 }
 
+/// feedback with unknown positions (written '?'), matching every feedback that
+/// agrees on the known ones
+#[derive(Hash, PartialEq, Eq, Clone, Copy)]
+pub struct PFeedback {
+  // green + yellow + known bitsets
+  g_bs: u16,
+  y_bs: u16,
+  k_bs: u16,
+  wlen: u8,
+}
+
+impl PFeedback {
+  pub fn from_str(s: &str) -> Option<Self> {
+    let wlen = s.len() as u8;
+    if wlen > MAXWLEN as u8 {
+      return None;
+    }
+    let mut pfb = PFeedback {
+      g_bs: 0,
+      y_bs: 0,
+      k_bs: 0,
+      wlen,
+    };
+    for (i, c) in s.to_ascii_uppercase().chars().enumerate() {
+      match c {
+        'G' => pfb.g_bs |= 1 << i,
+        'Y' => pfb.y_bs |= 1 << i,
+        'B' => {}
+        '?' => continue,
+        _ => return None,
+      }
+      pfb.k_bs |= 1 << i;
+    }
+    Some(pfb)
+  }
+
+  /// the feedback, if no positions are unknown
+  pub fn known(&self) -> Option<Feedback> {
+    if self.k_bs as u32 == (1u32 << self.wlen) - 1 {
+      Some(Feedback { g_bs: self.g_bs, y_bs: self.y_bs, wlen: self.wlen })
+    } else {
+      None
+    }
+  }
+
+  pub fn matches(&self, fb: &Feedback) -> bool {
+    fb.wlen == self.wlen
+      && ((fb.g_bs ^ self.g_bs) | (fb.y_bs ^ self.y_bs)) & self.k_bs == 0
+  }
+
+  pub fn matches_id(&self, id: u32) -> bool {
+    self.matches(&Feedback::from_id(id, self.wlen))
+  }
+}
+
+impl From<Feedback> for PFeedback {
+  fn from(fb: Feedback) -> Self {
+    let k_bs = ((1u32 << fb.wlen) - 1) as u16;
+    PFeedback { g_bs: fb.g_bs, y_bs: fb.y_bs, k_bs, wlen: fb.wlen }
+  }
+}
+
+impl fmt::Display for PFeedback {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let fb = Feedback { g_bs: self.g_bs, y_bs: self.y_bs, wlen: self.wlen };
+    let s: String = fb.to_string()
+      .chars()
+      .enumerate()
+      .map(|(i, c)| if self.k_bs & 1 << i != 0 { c } else { '?' })
+      .collect();
+    write!(f, "{}", s)
+  }
+}
+
+impl fmt::Debug for PFeedback {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self)
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct WBank {
   pub data: Vec<Word>,
   pub wlen: u8,
+  // answer weights from the optional fourth column (default 1)
+  pub weights: Vec<u32>,
 }
 
 impl WBank {
+  /// guess and answer banks from lines of "word,bank,wlen[,weight]", where
+  /// bank is A for answers (which are also guesses) and weight is a count or
+  /// frequency (see scale_weights); answers with weight 0 are only guesses
   pub fn from2<P>(p: P, wlen: u8) -> io::Result<(Self, Self)>
   where
     P: AsRef<Path>, {
@@ -272,21 +369,41 @@ impl WBank {
     let reader = BufReader::new(file);
     let mut gdata = Vec::<Word>::new();
     let mut adata = Vec::<Word>::new();
-    for line in reader.lines().skip(1).flatten() {
+    let mut aweights = Vec::<f64>::new();
+    for (i, line) in reader.lines().enumerate().skip(1) {
+      let line = line?;
+      if line.trim().is_empty() {
+        continue;
+      }
+      let bad = |what: &str| {
+        Error::new(ErrorKind::InvalidData, format!("line {} has an invalid {}", i + 1, what))
+      };
       // parse line
-      let vec: Vec<&str> = line.split(',').collect();
-      if vec[2].parse::<u8>().unwrap() != wlen {
+      let vec: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+      let wlen2 = vec.get(2).and_then(|s| s.parse::<u8>().ok()).ok_or_else(|| bad("word length"))?;
+      if wlen2 != wlen {
         continue;
       }
-      // push to both if answer word, but only guess if guess word
-      let w = Word::from_str(vec[0]).unwrap();
-      if vec[1] == "A" {
-        adata.push(w)
+      let w = Word::from_str(vec[0]).filter(|w| w.wlen == wlen).ok_or_else(|| bad("word"))?;
+      let weight = match vec.get(3).filter(|s| !s.is_empty()) {
+        None => 1.,
+        Some(s) => s.parse::<f64>()
+          .ok()
+          .filter(|x| x.is_finite() && *x >= 0.)
+          .ok_or_else(|| bad("weight"))?,
+      };
+      // push to both if answer word with weight, but only guess if guess word
+      if vec[1] == "A" && weight > 0. {
+        adata.push(w);
+        aweights.push(weight);
       }
       gdata.push(w);
     }
 
-    Ok((WBank { data: gdata, wlen }, WBank { data: adata, wlen }))
+    let gweights = vec![1; gdata.len()];
+    let aweights = scale_weights(&aweights);
+    Ok((WBank { data: gdata, wlen, weights: gweights },
+        WBank { data: adata, wlen, weights: aweights }))
   }
 
   pub fn len(&self) -> usize {
@@ -297,6 +414,7 @@ impl WBank {
     WBank {
       data: Vec::new(),
       wlen: 0,
+      weights: Vec::new(),
     }
   }
 
@@ -304,6 +422,7 @@ impl WBank {
     WBank {
       data: Vec::new(),
       wlen,
+      weights: Vec::new(),
     }
   }
 
@@ -311,6 +430,14 @@ impl WBank {
     self.data.contains(&w)
   }
 
+  /// weights by word if any aren't 1
+  pub fn weight_map(&self) -> Option<HashMap<Word, u32>> {
+    if self.weights.iter().all(|&x| x == 1) {
+      return None;
+    }
+    Some(self.data.iter().cloned().zip(self.weights.iter().cloned()).collect())
+  }
+
   pub fn pick(&self, rng: &mut ThreadRng, n: usize) -> Vec<Word> {
     self.data.choose_multiple(rng, n).cloned().collect()
   }
@@ -326,8 +453,93 @@ impl WBank {
   }
 }
 
+/// weights as integers: kept if they are and sum to at most MAXWSUM, otherwise
+/// scaled to sum to about MAXWSUM (nonzero weights to at least 1)
+pub fn scale_weights(xs: &[f64]) -> Vec<u32> {
+  let sum: f64 = xs.iter().sum();
+  if xs.iter().all(|x| x.fract() == 0.) && sum <= MAXWSUM as f64 {
+    return xs.iter().map(|x| *x as u32).collect();
+  }
+  xs.iter()
+    .map(|x| match (x / sum * MAXWSUM as f64).round() as u32 {
+      0 if *x > 0. => 1,
+      w => w,
+    })
+    .collect()
+}
+
 pub type FbMap<T> = HashMap<Feedback, T>;
 
+/// which board's hints a guess must be consistent with in multi-board hard mode
+/// (boards with different hints conflict, so no guess is consistent with them all)
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum HardRule {
+  /// any unfinished board's
+  Any,
+  /// the first unfinished board's
+  First,
+}
+
+impl HardRule {
+  pub fn from_str(s: &str) -> Option<Self> {
+    match s {
+      "any" => Some(HardRule::Any),
+      "first" => Some(HardRule::First),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for HardRule {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      HardRule::Any => write!(f, "any"),
+      HardRule::First => write!(f, "first"),
+    }
+  }
+}
+
+/// decision tree file formats
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TreeFormat {
+  /// versioned, indented text
+  Text,
+  /// versioned json
+  Json,
+  /// Alex Selby's line per answer
+  Selby,
+}
+
+impl TreeFormat {
+  pub fn from_str(s: &str) -> Option<Self> {
+    match s {
+      "text" => Some(TreeFormat::Text),
+      "json" => Some(TreeFormat::Json),
+      "selby" => Some(TreeFormat::Selby),
+      _ => None,
+    }
+  }
+
+  /// json if the path ends in ".json", otherwise text
+  pub fn from_path<P: AsRef<Path>>(p: P) -> Self {
+    if p.as_ref().extension().is_some_and(|ext| ext == "json") {
+      TreeFormat::Json
+    } else {
+      TreeFormat::Text
+    }
+  }
+}
+
+impl fmt::Display for TreeFormat {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TreeFormat::Text => write!(f, "text"),
+      TreeFormat::Json => write!(f, "json"),
+      TreeFormat::Selby => write!(f, "selby"),
+    }
+  }
+}
+
 // decision tree
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DTree {
@@ -335,6 +547,8 @@ pub enum DTree {
   Node {
     // total leaf depth
     tot: u32,
+    // weight of answers found at each depth
+    hist: Vec<u32>,
     // word
     word: Word,
     // children per unique feedback
@@ -343,57 +557,524 @@ pub enum DTree {
 }
 
 impl DTree {
+  /// make node, summarizing children (weight is the word's, if an answer)
+  pub fn node(word: Word, fbmap: FbMap<DTree>, weight: u32) -> Self {
+    let mut hist = vec![0];
+    for dt in fbmap.values() {
+      match dt {
+        DTree::Leaf => hist[0] += weight,
+        DTree::Node { hist: hist2, .. } => {
+          if hist.len() < hist2.len() + 1 {
+            hist.resize(hist2.len() + 1, 0);
+          }
+          for (i, x) in hist2.iter().enumerate() {
+            hist[i + 1] += x;
+          }
+        }
+      }
+    }
+    let tot = hist.iter()
+      .enumerate()
+      .map(|(i, x)| (i as u32 + 1) * x)
+      .sum();
+    DTree::Node { tot, hist, word, fbmap }
+  }
+
   pub fn follow(&self, fb: Feedback) -> Option<&DTree> {
     match self {
       DTree::Leaf => None,
-      DTree::Node {
-        tot: _,
-        word: _,
-        fbmap,
-      } => fbmap.get(&fb),
+      DTree::Node { fbmap, .. } => fbmap.get(&fb),
     }
   }
 
   pub fn get_tot(&self) -> u32 {
     match self {
       DTree::Leaf => 0,
-      DTree::Node {
-        tot,
-        word: _,
-        fbmap: _,
-      } => *tot,
+      DTree::Node { tot, .. } => *tot,
+    }
+  }
+
+  /// weight of answers found at each depth
+  pub fn get_hist(&self) -> &[u32] {
+    match self {
+      DTree::Leaf => &[],
+      DTree::Node { hist, .. } => hist,
     }
   }
 
   pub fn get_fbmap(&self) -> Option<&FbMap<DTree>> {
     match self {
       DTree::Leaf => None,
-      DTree::Node {
-        tot: _,
-        word: _,
-        fbmap,
-      } => Some(fbmap),
+      DTree::Node { fbmap, .. } => Some(fbmap),
     }
   }
 
-  pub fn pprint<W>(&self, out: &mut W, indent: &String, n: u32)
-  where
-    W: Write, {
+  /// answers the tree finds
+  pub fn answers(&self) -> Vec<Word> {
+    let mut aws = Vec::new();
+    self.push_answers(&mut aws);
+    aws
+  }
+
+  fn push_answers(&self, aws: &mut Vec<Word>) {
+    if let DTree::Node { word, fbmap, .. } = self {
+      for (fb, dt) in fbmap.iter() {
+        if fb.is_correct() {
+          aws.push(*word);
+        }
+        dt.push_answers(aws);
+      }
+    }
+  }
+
+  /// most guesses any answer takes
+  pub fn depth(&self) -> usize {
+    self.get_hist().len()
+  }
+
+  /// distinct words the tree guesses
+  pub fn words(&self) -> HashSet<Word> {
+    let mut ws = HashSet::new();
+    self.push_words(&mut ws);
+    ws
+  }
+
+  fn push_words(&self, ws: &mut HashSet<Word>) {
+    if let DTree::Node { word, fbmap, .. } = self {
+      ws.insert(*word);
+      for dt in fbmap.values() {
+        dt.push_words(ws);
+      }
+    }
+  }
+
+  /// the guesses and feedback the tree plays for an answer, if it finds it
+  pub fn trace(&self, aw: Word) -> Option<Vec<(Word, Feedback)>> {
+    let mut line = Vec::new();
+    let mut node = self;
+    while let DTree::Node { word, fbmap, .. } = node {
+      let fb = Feedback::from(*word, aw)?;
+      node = fbmap.get(&fb)?;
+      line.push((*word, fb));
+    }
+    line.last().filter(|(_, fb)| fb.is_correct())?;
+    Some(line)
+  }
+
+  /// problems with the tree as a strategy for aws: answers whose feedback
+  /// isn't followed, feedback no answer gives, guesses past the turns limit,
+  /// and (if hard) guesses breaking an earlier hint
+  pub fn verify(&self, aws: &[Word], turns: u32, hard: bool) -> Vec<String> {
+    let mut errs = Vec::new();
     match self {
-      DTree::Leaf => {}
-      DTree::Node { tot, word, fbmap } => {
-        writeln!(out, "{}{}, {}", indent, word.to_string(), tot);
-        let mut indent2 = indent.clone();
-        indent2.push(' ');
+      DTree::Leaf if !aws.is_empty() => {
+        errs.push(format!("the tree is empty, but there are {} answers", aws.len()));
+      }
+      DTree::Leaf => (),
+      DTree::Node { .. } => self.verify_node(aws, turns, hard, &mut Vec::new(), &mut errs),
+    }
+    errs
+  }
+
+  fn verify_node(&self, aws: &[Word], turns: u32, hard: bool,
+                 path: &mut Vec<(Word, Feedback)>, errs: &mut Vec<String>) {
+    let (word, fbmap) = match self {
+      DTree::Leaf => return,
+      DTree::Node { word, fbmap, .. } => (*word, fbmap),
+    };
+    let after = if path.is_empty() {
+      "the start".to_owned()
+    } else {
+      path.iter().map(|(gw, fb)| format!("{}.{}", gw, fb)).collect::<Vec<String>>().join(".")
+    };
+    let turn = path.len() as u32 + 1;
+    if turn > turns {
+      errs.push(format!("after {}: {} is guess {}, past the {} turns", after, word, turn, turns));
+      return;
+    }
+    if hard && path.iter().any(|(gw, fb)| Feedback::from(*gw, word) != Some(*fb)) {
+      errs.push(format!("after {}: {} breaks hard mode", after, word));
+    }
+
+    // partition answers by feedback
+    let mut parts: FbMap<Vec<Word>> = FbMap::new();
+    for aw in aws {
+      match Feedback::from(word, *aw) {
+        Some(fb) => parts.entry(fb).or_default().push(*aw),
+        None => errs.push(format!("{} doesn't have {}'s length", aw, word)),
+      }
+    }
+    let mut fbs: Vec<Feedback> = parts.keys().chain(fbmap.keys()).cloned().collect();
+    fbs.sort_by_key(|fb| fb.to_id());
+    fbs.dedup();
+    for fb in fbs {
+      match (parts.get(&fb), fbmap.get(&fb)) {
+        (Some(aws2), None) => errs.push(format!(
+          "after {}: {} gives {} for {}, which isn't in the tree",
+          after,
+          word,
+          fb,
+          aws2.iter().map(|aw| aw.to_string()).collect::<Vec<String>>().join(", ")
+        )),
+        (None, Some(_)) => errs.push(format!("after {}: no answer gives {} to {}", after, fb, word)),
+        (Some(aws2), Some(dt)) => {
+          path.push((word, fb));
+          dt.verify_node(aws2, turns, hard, path, errs);
+          path.pop();
+        }
+        (None, None) => unreachable!(),
+      }
+    }
+  }
+
+  /// versioned text format: a "hustle-dtree VERSION" header, the root as
+  /// "WORD TOT", then each child on its own line one space deeper than its
+  /// parent, as "FB WORD TOT", or "FB ." where the answer is found (".W" if
+  /// its weight W isn't 1)
+  pub fn to_text(&self) -> String {
+    let mut lines = vec![format!("hustle-dtree {}", DTREE_VERSION)];
+    match self {
+      DTree::Leaf => lines.push(".".to_owned()),
+      DTree::Node { tot, word, .. } => {
+        lines.push(format!("{} {}", word, tot));
+        self.push_text(&mut lines, 1);
+      }
+    }
+    lines.join("\n") + "\n"
+  }
+
+  fn push_text(&self, lines: &mut Vec<String>, depth: usize) {
+    if let DTree::Node { hist, fbmap, .. } = self {
+      let indent = " ".repeat(depth);
+      let mut items: Vec<(&Feedback, &DTree)> = fbmap.iter().collect();
+      items.sort_by_key(|(fb, _)| fb.to_id());
+      for (fb, dt) in items {
+        match dt {
+          DTree::Leaf if hist[0] == 1 => lines.push(format!("{}{} .", indent, fb)),
+          DTree::Leaf => lines.push(format!("{}{} .{}", indent, fb, hist[0])),
+          DTree::Node { tot, word, .. } => {
+            lines.push(format!("{}{} {} {}", indent, fb, word, tot));
+            dt.push_text(lines, depth + 1);
+          }
+        }
+      }
+    }
+  }
+
+  /// parse the text format, ignoring blank lines and '#' comments
+  pub fn from_text(s: &str) -> Option<Self> {
+    let mut lines = s.lines()
+      .filter(|line| !line.trim().is_empty() && !line.starts_with('#'));
+    let version = lines.next()?
+      .strip_prefix("hustle-dtree ")?
+      .trim()
+      .parse::<u32>()
+      .ok()?;
+    if version != DTREE_VERSION {
+      return None;
+    }
+    let lines: Vec<(usize, Vec<&str>)> = lines
+      .map(|line| (line.len() - line.trim_start_matches(' ').len(), line.split_whitespace().collect()))
+      .collect();
+    let (depth, toks) = lines.first()?;
+    if *depth != 0 {
+      return None;
+    }
+    let mut i = 1;
+    let dt = match toks[..] {
+      ["."] => DTree::Leaf,
+      [word, tot] => Self::pop_text(&lines, &mut i, word, tot, 1)?,
+      _ => return None,
+    };
+    if i != lines.len() {
+      return None;
+    }
+    Some(dt)
+  }
+
+  // parse a node and its children at depth
+  fn pop_text(lines: &[(usize, Vec<&str>)], i: &mut usize, word: &str, tot: &str,
+              depth: usize) -> Option<Self> {
+    let word = Word::from_str(word)?;
+    let tot = tot.parse::<u32>().ok()?;
+    let mut fbmap = FbMap::new();
+    let mut weight = 1;
+    while let Some((depth2, toks)) = lines.get(*i) {
+      if *depth2 < depth {
+        break;
+      } else if *depth2 > depth {
+        return None;
+      }
+      *i += 1;
+      let fb = Feedback::from_str(toks.first()?)?;
+      let dt = match toks[1..] {
+        [leaf] => {
+          let leaf = leaf.strip_prefix('.')?;
+          weight = if leaf.is_empty() { 1 } else { leaf.parse().ok()? };
+          DTree::Leaf
+        }
+        [word2, tot2] => Self::pop_text(lines, i, word2, tot2, depth + 1)?,
+        _ => return None,
+      };
+      if (dt == DTree::Leaf) != fb.is_correct() || fbmap.insert(fb, dt).is_some() {
+        return None;
+      }
+    }
+    let dt = DTree::node(word, fbmap, weight);
+    if dt.get_tot() != tot {
+      return None;
+    }
+    Some(dt)
+  }
+
+  /// versioned json format: {"format": "hustle-dtree", "version": VERSION,
+  /// "tree": TREE}, where a node is {"word": WORD, "tot": TOT, "children":
+  /// {FB: TREE, ...}} and the answer being found is its weight
+  pub fn to_json(&self) -> Value {
+    json!({
+      "format": "hustle-dtree",
+      "version": DTREE_VERSION,
+      "tree": self.tree_json(1),
+    })
+  }
+
+  fn tree_json(&self, weight: u32) -> Value {
+    match self {
+      DTree::Leaf => weight.into(),
+      DTree::Node { tot, hist, word, fbmap } => {
         let mut items: Vec<(&Feedback, &DTree)> = fbmap.iter().collect();
-        items.sort_by_key(|(fb, dt)| fb.to_id());
+        items.sort_by_key(|(fb, _)| fb.to_id());
+        let children: Map<String, Value> = items.iter()
+          .map(|(fb, dt)| (fb.to_string(), dt.tree_json(hist[0])))
+          .collect();
+        json!({
+          "word": word.to_string(),
+          "tot": tot,
+          "children": children,
+        })
+      }
+    }
+  }
+
+  pub fn from_json(js: &Value) -> Option<Self> {
+    if js.get("format")?.as_str()? != "hustle-dtree"
+      || js.get("version")?.as_u64()? != DTREE_VERSION as u64 {
+      return None;
+    }
+    let (dt, _) = Self::tree_from_json(js.get("tree")?)?;
+    Some(dt)
+  }
+
+  // also returns leaf weight
+  fn tree_from_json(js: &Value) -> Option<(Self, u32)> {
+    if let Some(weight) = js.as_u64() {
+      return Some((DTree::Leaf, u32::try_from(weight).ok()?));
+    }
+    let word = Word::from_str(js.get("word")?.as_str()?)?;
+    let tot = u32::try_from(js.get("tot")?.as_u64()?).ok()?;
+    let mut fbmap = FbMap::new();
+    let mut weight = 1;
+    for (k, v) in js.get("children")?.as_object()? {
+      let fb = Feedback::from_str(k)?;
+      let (dt, weight2) = Self::tree_from_json(v)?;
+      if dt == DTree::Leaf {
+        weight = weight2;
+      }
+      if (dt == DTree::Leaf) != fb.is_correct() || fbmap.insert(fb, dt).is_some() {
+        return None;
+      }
+    }
+    let dt = DTree::node(word, fbmap, weight);
+    if dt.get_tot() != tot {
+      return None;
+    }
+    Some((dt, 1))
+  }
+
+  /// Alex Selby's format: a line per answer, with each guess on the way
+  /// followed by its feedback and turn, as "salet BBBBB1 courd BYBBB2 ...
+  /// aback GGGGG4" (answer weights aren't kept)
+  pub fn to_selby(&self) -> String {
+    let mut lines = Vec::new();
+    self.push_selby(&mut lines, &mut Vec::new());
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+  }
+
+  fn push_selby(&self, lines: &mut Vec<String>, path: &mut Vec<String>) {
+    if let DTree::Node { word, fbmap, .. } = self {
+      let mut items: Vec<(&Feedback, &DTree)> = fbmap.iter().collect();
+      items.sort_by_key(|(fb, _)| fb.to_id());
+      for (fb, dt) in items {
+        path.push(format!("{} {}{}", word.to_string().to_lowercase(), fb, path.len() + 1));
+        match dt {
+          DTree::Leaf => lines.push(path.join(" ")),
+          DTree::Node { .. } => dt.push_selby(lines, path),
+        }
+        path.pop();
+      }
+    }
+  }
+
+  /// parse Selby's format, where a line starting with spaces takes those
+  /// columns from the line above (every answer's weight is 1)
+  pub fn from_selby(s: &str) -> Option<Self> {
+    let mut paths: Vec<Vec<(Word, Feedback)>> = Vec::new();
+    let mut prev = String::new();
+    for line in s.lines().filter(|line| !line.trim().is_empty()) {
+      let indent = line.len() - line.trim_start().len();
+      let line = format!("{}{}", prev.get(..indent)?, &line[indent..]);
+      let toks: Vec<&str> = line.split_whitespace().collect();
+      if toks.is_empty() || !toks.len().is_multiple_of(2) {
+        return None;
+      }
+      let mut path = Vec::new();
+      for (i, pair) in toks.chunks(2).enumerate() {
+        let word = Word::from_str(pair[0])?;
+        let (fb, turn) = (pair[1].get(..word.wlen as usize)?, pair[1].get(word.wlen as usize..)?);
+        let fb = Feedback::from_str(fb)?;
+        // only the last guess is the answer
+        if turn.parse::<usize>().ok()? != i + 1 || fb.is_correct() != (i + 1 == toks.len() / 2) {
+          return None;
+        }
+        path.push((word, fb));
+      }
+      paths.push(path);
+      prev = line;
+    }
+    let paths: Vec<&[(Word, Feedback)]> = paths.iter().map(|path| &path[..]).collect();
+    Self::from_paths(&paths)
+  }
+
+  // the tree of paths all starting with the same guess
+  fn from_paths(paths: &[&[(Word, Feedback)]]) -> Option<Self> {
+    let word = paths.first()?.first()?.0;
+    let mut groups: FbMap<Vec<&[(Word, Feedback)]>> = FbMap::new();
+    for path in paths {
+      let ((word2, fb), rest) = path.split_first()?;
+      if *word2 != word {
+        return None;
+      }
+      groups.entry(*fb).or_default().push(rest);
+    }
+    let mut fbmap = FbMap::new();
+    for (fb, rests) in groups {
+      let dt = if fb.is_correct() {
+        // each answer once
+        if rests.len() != 1 {
+          return None;
+        }
+        DTree::Leaf
+      } else {
+        Self::from_paths(&rests)?
+      };
+      fbmap.insert(fb, dt);
+    }
+    Some(DTree::node(word, fbmap, 1))
+  }
+
+  /// save in the format the path's extension suggests
+  pub fn save<P: AsRef<Path>>(&self, p: P) -> io::Result<()> {
+    let fmt = TreeFormat::from_path(&p);
+    self.save_as(p, fmt)
+  }
+
+  pub fn save_as<P: AsRef<Path>>(&self, p: P, fmt: TreeFormat) -> io::Result<()> {
+    let s = match fmt {
+      TreeFormat::Text => self.to_text(),
+      TreeFormat::Json => self.to_json().to_string() + "\n",
+      TreeFormat::Selby => self.to_selby(),
+    };
+    std::fs::write(p, s)
+  }
+
+  /// which format a tree file's contents are in
+  pub fn detect(s: &str) -> TreeFormat {
+    let first = s.lines()
+      .map(|line| line.trim())
+      .find(|line| !line.is_empty() && !line.starts_with('#'))
+      .unwrap_or("");
+    if first.starts_with('{') {
+      TreeFormat::Json
+    } else if first.starts_with("hustle-dtree") {
+      TreeFormat::Text
+    } else {
+      TreeFormat::Selby
+    }
+  }
+
+  /// load any format
+  pub fn load<P: AsRef<Path>>(p: P) -> io::Result<Self> {
+    let s = std::fs::read_to_string(p)?;
+    let dt = match Self::detect(&s) {
+      TreeFormat::Json => serde_json::from_str(&s).ok().and_then(|js| Self::from_json(&js)),
+      TreeFormat::Text => Self::from_text(&s),
+      TreeFormat::Selby => Self::from_selby(&s),
+    };
+    dt.ok_or_else(|| Error::other("invalid tree file!"))
+  }
+
+  /// single line pre-order encoding ("WORD TOT N FB CHILD ..." or "." for leaves,
+  /// followed by the answer's weight if not 1)
+  pub fn to_compact(&self) -> String {
+    let mut toks = Vec::new();
+    self.push_compact(&mut toks, 1);
+    toks.join(" ")
+  }
+
+  fn push_compact(&self, toks: &mut Vec<String>, weight: u32) {
+    match self {
+      DTree::Leaf if weight == 1 => toks.push(".".to_owned()),
+      DTree::Leaf => toks.push(format!(".{}", weight)),
+      DTree::Node { tot, hist, word, fbmap } => {
+        toks.push(word.to_string());
+        toks.push(tot.to_string());
+        toks.push(fbmap.len().to_string());
+        let mut items: Vec<(&Feedback, &DTree)> = fbmap.iter().collect();
+        items.sort_by_key(|(fb, _)| fb.to_id());
         for (fb, dt) in items {
-          writeln!(out, "{}{}{}", indent2, fb.to_string(), n);
-          dt.pprint(out, &indent2, n + 1);
+          toks.push(fb.to_string());
+          dt.push_compact(toks, hist[0]);
         }
       }
     }
   }
+
+  pub fn from_compact(s: &str) -> Option<Self> {
+    let mut toks = s.split_whitespace();
+    let (dt, _) = Self::pop_compact(&mut toks)?;
+    if toks.next().is_some() {
+      return None;
+    }
+    Some(dt)
+  }
+
+  // also returns leaf weight
+  fn pop_compact<'a, I>(toks: &mut I) -> Option<(Self, u32)>
+  where I: Iterator<Item = &'a str>, {
+    let tok = toks.next()?;
+    if let Some(weight) = tok.strip_prefix('.') {
+      let weight = if weight.is_empty() { 1 } else { weight.parse().ok()? };
+      return Some((DTree::Leaf, weight));
+    }
+    let word = Word::from_str(tok)?;
+    let tot = toks.next()?.parse::<u32>().ok()?;
+    let n = toks.next()?.parse::<usize>().ok()?;
+    let mut fbmap = FbMap::new();
+    let mut weight = 0;
+    for _ in 0..n {
+      let fb = Feedback::from_str(toks.next()?)?;
+      let (dt, weight2) = Self::pop_compact(toks)?;
+      if dt == DTree::Leaf {
+        weight = weight2;
+      }
+      fbmap.insert(fb, dt);
+    }
+    let dt = DTree::node(word, fbmap, weight);
+    if dt.get_tot() != tot {
+      return None;
+    }
+    Some((dt, 1))
+  }
 }
 
 pub struct Range<X> where X: Copy + SampleUniform {
@@ -428,7 +1109,7 @@ where X: Copy + FromStr + SampleUniform {
   
   fn from_str(s: &str) -> Result<Self, Self::Err> {
     lazy_static! {
-      static ref RE_UNIF: Regex = Regex::new(r"^(\d+)..(=?)(\d+)$").unwrap();
+      static ref RE_UNIF: Regex = Regex::new(r"^(\d+(?:\.\d+)?)\.\.(=?)(\d+(?:\.\d+)?)$").unwrap();
     }
 
     if let Some(caps) = RE_UNIF.captures(s) {
@@ -482,5 +1163,66 @@ mod test {
     assert_eq!(fb2, Feedback::from_id(id2, 5));
     assert_eq!(id1, fb1.to_id());
     assert_eq!(id2, fb2.to_id());
+
+    // up to MAXWLEN letters
+    let w5 = Word::from_str("characteristics").unwrap();
+    let w6 = Word::from_str("thermodynamical").unwrap();
+    let fb3 = Feedback::from(w5, w6).unwrap();
+    assert_eq!(Feedback::from_id(fb_id(w5, w6), 15), fb3);
+    assert_eq!(Feedback::from(w5, w5).unwrap(), Feedback::correct(15));
+    assert!(Feedback::correct(MAXWLEN as u8).is_correct());
+    assert!(!fb3.is_correct());
+    assert!(Word::from_str("abcdefghijklmnopq").is_none());
+    assert!(Word::from_str("sal3t").is_none());
+    assert!(Feedback::from_str("bgx").is_none());
+    assert!(Feedback::from_str("bg?").is_none());
+  }
+
+  #[test]
+  pub fn partial_feedback() {
+    let fb = Feedback::from_str("ygbbg").unwrap();
+    let pfb1 = PFeedback::from_str("y?bb?").unwrap();
+    let pfb2 = PFeedback::from_str("?????").unwrap();
+    let pfb3 = PFeedback::from_str("yybb?").unwrap();
+
+    assert!(pfb1.matches(&fb));
+    assert!(pfb2.matches(&fb));
+    assert!(!pfb3.matches(&fb));
+    assert!(pfb1.matches_id(fb.to_id()));
+    assert!(!pfb1.matches(&Feedback::from_str("ygbbgb").unwrap()));
+
+    // fully known feedback only matches itself
+    assert_eq!(PFeedback::from(fb).known(), Some(fb));
+    assert_eq!(PFeedback::from_str("ygbbg").unwrap(), PFeedback::from(fb));
+    assert!(!PFeedback::from(fb).matches(&Feedback::from_str("ygbbb").unwrap()));
+    assert_eq!(pfb1.known(), None);
+    assert_eq!(pfb1.to_string(), "Y?BB?");
+  }
+
+  #[test]
+  pub fn bank_weights() {
+    let path = std::env::temp_dir().join("hustle_bank_test.csv");
+    let load = |s: &str| {
+      std::fs::write(&path, s).unwrap();
+      WBank::from2(&path, 5)
+    };
+
+    // counts are kept, zero weights are only guesses
+    let (gwb, awb) = load("word,bank,wlen,weight\nsalet,A,5,3\ncourd,A,5,0\nnymph,A,5\ntrace,G,5\n").unwrap();
+    assert_eq!(gwb.len(), 4);
+    assert_eq!(awb.data, vec![Word::from_str("salet").unwrap(), Word::from_str("nymph").unwrap()]);
+    assert_eq!(awb.weights, vec![3, 1]);
+
+    // frequencies and big counts are scaled
+    let (_, awb) = load("word,bank,wlen,weight\nsalet,A,5,0.75\ncourd,A,5,0.25\nnymph,A,5,1e-12\n").unwrap();
+    assert_eq!(awb.weights, vec![MAXWSUM / 4 * 3, MAXWSUM / 4, 1]);
+    let (_, awb) = load("word,bank,wlen,weight\nsalet,A,5,3000000000\ncourd,A,5,1000000000\n").unwrap();
+    assert_eq!(awb.weights, vec![MAXWSUM / 4 * 3, MAXWSUM / 4]);
+
+    // malformed lines are errors
+    for bad in ["salet,A,5,x", "salet,A,5,-1", "salet,A,5,inf", "sal3t,A,5", "salet,A", "salet,A,x"] {
+      assert!(load(&format!("word,bank,wlen,weight\n{}\n", bad)).is_err(), "{}", bad);
+    }
+    std::fs::remove_file(&path).unwrap();
   }
 }
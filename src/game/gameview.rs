@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use std::path::Path;
 use std::cmp::min;
@@ -24,14 +25,34 @@ enum State {
 }
 
 struct FbCol {
-  ans: Word,
+  // possible answers: just the picked one, or any left against the adversary
+  aws: Vec<Word>,
+  // feedback to each guess until done
+  fbs: Vec<Feedback>,
   done: bool,
 }
 
 impl FbCol {
+  fn new(aws: Vec<Word>) -> Self {
+    FbCol { aws, fbs: Vec::new(), done: false }
+  }
+
+  /// give the feedback keeping the most possible answers (the only feedback
+  /// with one answer), and return it
+  fn guess(&mut self, gw: Word) -> Feedback {
+    let mut counts = HashMap::<Feedback, usize>::new();
+    for aw in &self.aws {
+      *counts.entry(Feedback::from(gw, *aw).unwrap()).or_default() += 1;
+    }
+    let fb = adversary_fb(counts).unwrap();
+    self.aws.retain(|aw| Feedback::from(gw, *aw) == Some(fb));
+    self.fbs.push(fb);
+    self.done = fb.is_correct();
+    fb
+  }
+
   /// draw and return if correct
-  fn draw_guess(&self, gw: Word, pos: Vec2, printer: &Printer) -> bool {
-    let fb = Feedback::from(gw, self.ans).unwrap();
+  fn draw_guess(&self, gw: Word, fb: Feedback, pos: Vec2, printer: &Printer) -> bool {
     for j in 0..gw.wlen {
       let cs = if self.done && CONFIG.column_desaturate {
         let fg = CONFIG.color("dfb_fg");
@@ -67,6 +88,8 @@ pub struct GameView {
   wbp: String,
   nwords: usize,
   wlen: u8,
+  hard: Option<HardRule>,
+  adversary: bool,
   gwb: WBank,
   awb: WBank,
   fbcols: Vec<FbCol>,
@@ -85,14 +108,18 @@ pub struct GameView {
 }
 
 impl GameView {
-  pub fn new(wbn: &String, wlen: u8, nwords: usize) -> Self {
+  pub fn new(wbn: &String, wlen: u8, nwords: usize, hard: Option<HardRule>,
+             adversary: bool) -> Self {
     let wbp = CONFIG.word_banks.get(wbn).unwrap();
     let (gwb, awb) = WBank::from2(wbp, wlen).unwrap();
     let mut out = Self {
       wbn: wbn.clone(),
       wbp: wbp.clone(),
-      nwords,
+      // the adversary would play every board alike
+      nwords: if adversary { 1 } else { nwords },
       wlen,
+      hard,
+      adversary,
       gwb,
       awb,
       fbcols: Vec::<FbCol>::new(),
@@ -115,9 +142,15 @@ impl GameView {
 
   pub fn start(&mut self) {
     self.guesses.clear();
-    self.answers = self.awb.pick(&mut rand::thread_rng(), self.nwords);
-    self.fbcols = self.answers.iter()
-      .map(|ans| FbCol {ans: *ans, done: false}).collect();
+    if self.adversary {
+      // no answer until the adversary runs out of others
+      self.answers.clear();
+      self.fbcols = vec![FbCol::new(self.awb.data.clone())];
+    } else {
+      self.answers = self.awb.pick(&mut rand::thread_rng(), self.nwords);
+      self.fbcols = self.answers.iter()
+        .map(|ans| FbCol::new(vec![*ans])).collect();
+    }
     self.guessbuf.clear();
     self.state = State::Play;
     self.inst = Instant::now();
@@ -137,6 +170,9 @@ impl GameView {
     let gw = Word::from(self.guessbuf.clone()).unwrap();
     self.guessbuf = String::new();
     if !self.gwb.data.contains(&gw) {return}
+    if let Some(rule) = self.hard {
+      if !self.hard_allows(gw, rule) {return}
+    }
 
     // inst timing on first guess
     if self.guesses.is_empty() {
@@ -147,34 +183,51 @@ impl GameView {
     self.guesses.push(gw);
     self.turn += 1;
 
-    // update done's and ndone
-    let mut finished = None;
-    for (i, mut fbcol) in self.fbcols.iter_mut().enumerate() {
-      if !fbcol.done && fbcol.ans == gw {
-        fbcol.done = true;
+    // give feedback, updating done's and ndone
+    for fbcol in self.fbcols.iter_mut().filter(|fbc| !fbc.done) {
+      if fbcol.guess(gw).is_correct() {
         self.ndone += 1;
-        finished = Some(i);
-        break;
+        if self.adversary {
+          self.answers.push(gw);
+        }
       }
     }
 
     // remove if configured to do so
     if CONFIG.column_finish == "remove" {
-      if let Some(finished) = finished {
-        self.fbcols.remove(finished);   
-      }
+      self.fbcols.retain(|fbc| !fbc.done);
     }
 
     // update state
     if self.ndone == self.nwords {
-      self.state = State::Won;
-      self.time = self.inst.elapsed();
+      self.end(State::Won);
     } else if self.turn == self.nwords + NEXTRA {
-      self.state = State::Lost;
-      self.time = self.inst.elapsed();
+      self.end(State::Lost);
+    }
+  }
+
+  fn end(&mut self, state: State) {
+    // the adversary finally settles on an answer
+    if self.adversary {
+      let rest = self.fbcols.iter().filter(|fbc| !fbc.done).map(|fbc| fbc.aws[0]);
+      self.answers.extend(rest);
     }
+    self.state = state;
+    self.time = self.inst.elapsed();
   }
   
+  /// if a guess is consistent with the board(s) hard mode requires
+  fn hard_allows(&self, gw: Word, rule: HardRule) -> bool {
+    let mut open = self.fbcols.iter().filter(|fbc| !fbc.done);
+    let fits = |fbc: &FbCol| self.guesses.iter()
+      .zip(&fbc.fbs)
+      .all(|(pg, fb)| Feedback::from(*pg, gw) == Some(*fb));
+    match rule {
+      HardRule::Any => open.any(fits),
+      HardRule::First => open.next().is_none_or(fits),
+    }
+  }
+
   fn draw_status(&self, printer: &Printer) {
     let limit = self.nwords + NEXTRA;
     let delta = (limit - self.turn) as isize - (self.nwords - self.ndone) as isize;
@@ -213,9 +266,9 @@ impl GameView {
     for ncol in 0..self.ncols {
       let x = (self.wlen+1) as usize * ncol + 1;
       if let Some(fbc) = self.fbcols.get(self.scroll*self.ncols+ncol) {
-        for (y, gw) in self.guesses.iter().enumerate().take(maxrow) {
+        for (y, (gw, fb)) in self.guesses.iter().zip(&fbc.fbs).enumerate().take(maxrow) {
           let pos: Vec2 = (x, y+3).into();
-          if fbc.draw_guess(*gw, pos, printer) {
+          if fbc.draw_guess(*gw, *fb, pos, printer) {
             break;
           }
         }
@@ -237,11 +290,13 @@ impl GameView {
 
     printer.print((1,1), "Results:");
     printer.print((1,2), &format!(
-        "{} on \"{}\" with wlen={}, nwords={}",
+        "{} on \"{}\" with wlen={}, nwords={}{}{}",
         s_result,
         self.wbn,
         self.wlen,
-        self.nwords));
+        self.nwords,
+        self.hard.map_or(String::new(), |rule| format!(", hard={}", rule)),
+        if self.adversary { ", adversary" } else { "" }));
     
     printer.print((1,4), "Statistics:");
     printer.print((1,5), &format!(
@@ -311,8 +366,7 @@ impl View for GameView {
         } Event::Key(Key::Down) => {
           self.scroll = (self.scroll + self.nrows + 1) % self.nrows;
         } Event::Key(Key::Esc) => {
-          self.state = State::Forfeit;
-          self.time = self.inst.elapsed();
+          self.end(State::Forfeit);
         } _ => {
           return EventResult::Ignored;
         }
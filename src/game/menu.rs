@@ -11,6 +11,7 @@ use cursive::theme::Color::*;
 use cursive::theme::BaseColor::*;
 use cursive::theme::{Theme, Palette, BorderStyle};
 
+use crate::ds::HardRule;
 use super::hselectview::HSelectView;
 use super::gameview::GameView;
 use super::editview::EditView;
@@ -22,6 +23,16 @@ pub fn open_menu(s: &mut Cursive) {
     bank_select.add_item(k.to_string(), v.to_string());
   }
 
+  let mut hard_select = HSelectView::new();
+  hard_select.add_item("off".to_string(), None);
+  for rule in [HardRule::Any, HardRule::First] {
+    hard_select.add_item(rule.to_string(), Some(rule));
+  }
+
+  let mut adversary_select = HSelectView::new();
+  adversary_select.add_item("off".to_string(), false);
+  adversary_select.add_item("on".to_string(), true);
+
   let menu_input = LinearLayout::vertical()
     .child(PaddedView::lrtb(0,0,1,1, TextView::new("HUSTLE").center()))
     .child(LinearLayout::horizontal()
@@ -41,6 +52,18 @@ pub fn open_menu(s: &mut Cursive) {
                   .fixed_width(10))
            .child(bank_select
                   .with_name("wbank")
+                  .fixed_width(15)))
+    .child(LinearLayout::horizontal()
+           .child(TextView::new("hard")
+                  .fixed_width(10))
+           .child(hard_select
+                  .with_name("hard")
+                  .fixed_width(15)))
+    .child(LinearLayout::horizontal()
+           .child(TextView::new("adversary")
+                  .fixed_width(10))
+           .child(adversary_select
+                  .with_name("adversary")
                   .fixed_width(15)));
 
   let menu = Dialog::around(menu_input)
@@ -63,10 +86,17 @@ fn menu_submit(s: &mut Cursive) {
   let wbn = s.call_on_name(
     "wbank",
     |view: &mut HSelectView<String>| view.selected_label());
+  let hard = s.call_on_name(
+    "hard",
+    |view: &mut HSelectView<Option<HardRule>>| view.selection());
+  let adversary = s.call_on_name(
+    "adversary",
+    |view: &mut HSelectView<bool>| view.selection());
 
-  if let (Some(nwords), Some(wlen), Some(Some(wbn))) = (nwords, wlen, wbn) {
+  if let (Some(nwords), Some(wlen), Some(Some(wbn)), Some(Some(hard)), Some(Some(adversary)))
+    = (nwords, wlen, wbn, hard, adversary) {
     s.pop_layer();
-    s.add_fullscreen_layer(GameView::new(&wbn, wlen, nwords));
+    s.add_fullscreen_layer(GameView::new(&wbn, wlen, nwords, *hard, *adversary));
   }
 }
 
@@ -8,22 +8,31 @@ use rand::distributions::Uniform;
 use regex::Regex;
 
 use std::str::FromStr;
+use std::iter::zip;
 use std::fs::{File, OpenOptions};
-use std::path::Path;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "solve")]
+use std::sync::Arc;
 
 mod ds;
 use crate::ds::*;
+use serde_json::json;
 mod command;
 use crate::command::{cli_parse, Commands};
+#[cfg(feature = "solve")]
+use crate::command::{BookCommands, TreeCommands};
 #[cfg(feature = "gen")]
 mod analysis;
 #[cfg(feature = "gen")]
-use analysis::{LGen, GGen};
+use analysis::{LGen, GGen, MGen};
 #[cfg(feature = "solve")]
 mod solve;
 #[cfg(feature = "solve")]
-use crate::solve::{Cache, SData, State, AData};
+use crate::solve::{Cache, MCache, SData, State, MData, MState, MTree, AData, FbTable, Book, BookKey, Objective, Stats};
+#[cfg(feature = "solve")]
+use crate::solve::book::{bank_key, data_key, default_book_path};
 #[cfg(feature = "play")]
 mod game;
 #[cfg(feature = "play")]
@@ -37,6 +46,205 @@ fn flatten_opt<T>(x: Option<Option<T>>) -> Option<T> {
   }
 }
 
+/// the line of guesses a tree with single feedbacks plays, as a gamestate
+#[cfg(feature = "solve")]
+fn dtree_line(dtree: &DTree) -> String {
+  let mut line = Vec::new();
+  let mut node = dtree;
+  while let DTree::Node { word, fbmap, .. } = node {
+    let (fb, dt) = fbmap.iter().next().unwrap();
+    line.push(format!("{}.{}", word, fb));
+    node = dt;
+  }
+  line.join(".")
+}
+
+/// follow a gamestate down a tree, returning the subtree and turn,
+/// or where they disagree
+#[cfg(feature = "solve")]
+fn walk_gamestate<'a>(mut dtree: &'a DTree, gamestate: &str) -> Result<(&'a DTree, u32), String> {
+  let mut turn = 0u32;
+  let mut it = gamestate.split('.');
+  while let Some(s_a) = it.next() {
+    if s_a.is_empty() {
+      break;
+    }
+    turn += 1;
+    let word = match dtree {
+      DTree::Node { word, .. } => *word,
+      DTree::Leaf => return Err(format!("guess {} ({}) is after the answer was found", turn, s_a)),
+    };
+    if s_a.len() != word.wlen as usize {
+      return Err(format!("guess {} ({}) should have {} letters", turn, s_a, word.wlen));
+    }
+    let gw = Word::from_str(s_a)
+      .ok_or_else(|| format!("guess {} ({}) should only have letters", turn, s_a))?;
+    if gw != word {
+      return Err(format!("the tree guesses {} on turn {}, not {}", word, turn, gw));
+    }
+    if let Some(s_b) = it.next() {
+      let fb = parse_feedback(s_b, turn, word.wlen as u32)?
+        .known()
+        .ok_or_else(|| format!("feedback {} ({}) can't have unknowns in a tree", turn, s_b))?;
+      dtree = dtree.follow(fb)
+        .ok_or_else(|| format!("feedback {} ({}) to {} isn't in the tree", turn, fb, gw))?;
+    }
+  }
+  Ok((dtree, turn))
+}
+
+/// load the word banks, or exit with why not
+fn load_banks(wbp: &str, wlen: u8) -> (WBank, WBank) {
+  let (gwb, awb) = WBank::from2(wbp, wlen)
+    .unwrap_or_else(|err| exit_with(&format!("couldn't load word bank {}: {}", wbp, err)));
+  if awb.data.is_empty() {
+    exit_with(&format!("no answers of length {} in bank", wlen));
+  }
+  (gwb, awb)
+}
+
+// whether errors are printed as json, for solve --format json
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// print an error and exit (in json mode, as {"error": ERR} on stdout)
+fn exit_with(err: &str) -> ! {
+  if JSON_ERRORS.load(Ordering::Relaxed) {
+    println!("{}", json!({"error": err}));
+  } else {
+    eprintln!("error: {}", err);
+  }
+  std::process::exit(1);
+}
+
+/// parse a gamestate's guess, which must be in the word bank unless dirty
+#[cfg(feature = "solve")]
+fn parse_guess(s: &str, turn: u32, wlen: u32, fbt: &FbTable, dirty: bool) -> Result<Word, String> {
+  if s.len() != wlen as usize {
+    return Err(format!("guess {} ({}) should have {} letters", turn, s, wlen));
+  }
+  let gw = Word::from_str(s)
+    .ok_or_else(|| format!("guess {} ({}) should only have letters", turn, s))?;
+  if !dirty && fbt.gindex(&gw).is_none() {
+    return Err(format!("guess {} ({}) isn't in the word bank (allow with --dirty)", turn, gw));
+  }
+  Ok(gw)
+}
+
+/// parse a gamestate's feedback
+#[cfg(feature = "solve")]
+fn parse_feedback(s: &str, turn: u32, wlen: u32) -> Result<PFeedback, String> {
+  if s.len() != wlen as usize {
+    return Err(format!("feedback {} ({}) should have {} letters", turn, s, wlen));
+  }
+  PFeedback::from_str(s)
+    .ok_or_else(|| format!("feedback {} ({}) should only have g, y, b or ?", turn, s))
+}
+
+/// follow gamestate, returning the state, last given word, and turn,
+/// or which guess or feedback is invalid
+#[cfg(feature = "solve")]
+fn follow_gamestate(mut state: State, gamestate: &str, dirty: bool)
+                    -> Result<(State, Option<Word>, u32), String> {
+  let mut w: Option<Word> = None;
+  let mut turn = 0u32;
+  let mut solved = false;
+  let mut it = gamestate.split('.');
+  while let Some(s_a) = it.next() {
+    if s_a.is_empty() {
+      break;
+    }
+    turn += 1;
+    let gw = parse_guess(s_a, turn, state.wlen, &state.fbt, dirty)?;
+    if solved {
+      return Err(format!("guess {} ({}) is after the answer was found", turn, gw));
+    } else if state.n == 0 {
+      return Err(format!("guess {} ({}) is past the last turn", turn, gw));
+    } else if !dirty && !state.allows(&gw) {
+      return Err(format!("guess {} ({}) doesn't use every hint, as hard mode requires", turn, gw));
+    }
+    if let Some(s_b) = it.next() {
+      let fb = parse_feedback(s_b, turn, state.wlen)?;
+      let alen = state.aws.len();
+      state = state.fb_follow(gw, fb);
+      solved = fb.known().is_some_and(|fb| fb.is_correct());
+      if state.aws.is_empty() && solved {
+        return Err(format!(
+          "feedback {} ({}) says {} is the answer, but it isn't one of the {} left", turn, fb, gw, alen
+        ));
+      } else if state.aws.is_empty() {
+        return Err(format!(
+          "feedback {} ({}) to {} fits none of the {} answers left", turn, fb, gw, alen
+        ));
+      }
+    } else {
+      w = Some(gw);
+    }
+  }
+  Ok((state, w, turn))
+}
+
+/// follow multi-board gamestate, returning the state, last given word, and turn,
+/// or which guess or feedback is invalid
+#[cfg(feature = "solve")]
+fn follow_mgamestate(mut state: MState, gamestate: &str, dirty: bool)
+                     -> Result<(MState, Option<Word>, u32), String> {
+  let mut w: Option<Word> = None;
+  let mut turn = 0u32;
+  let mut it = gamestate.split('.');
+  while let Some(s_a) = it.next() {
+    if s_a.is_empty() {
+      break;
+    }
+    turn += 1;
+    let gw = parse_guess(s_a, turn, state.wlen, &state.fbt, dirty)?;
+    if state.finished.iter().all(|&fin| fin) {
+      return Err(format!("guess {} ({}) is after every board was solved", turn, gw));
+    } else if state.turns == 0 {
+      return Err(format!("guess {} ({}) is past the last turn", turn, gw));
+    } else if !dirty && !state.allows(&gw) {
+      return Err(format!("guess {} ({}) doesn't use the hints hard mode requires", turn, gw));
+    }
+    if let Some(s_b) = it.next() {
+      let fbs = s_b.split(',')
+        .map(|s| parse_feedback(s, turn, state.wlen)?
+             .known()
+             .ok_or_else(|| format!("feedback {} ({}) can't have unknowns with several boards", turn, s)))
+        .collect::<Result<Vec<Feedback>, String>>()?;
+      if fbs.len() != state.nwords as usize {
+        return Err(format!("guess {} ({}) needs a feedback for each of the {} boards", turn, gw, state.nwords));
+      }
+      let alens: Vec<usize> = state.awss.iter().map(|aws| aws.len()).collect();
+      let finished = state.finished.clone();
+      state = state.fb_follow(gw, fbs.clone());
+      for (i, aws) in state.awss.iter().enumerate() {
+        if finished[i] && aws.is_empty() {
+          return Err(format!(
+            "feedback {} ({}) to {} doesn't fit board {}, which was already solved",
+            turn, fbs[i], gw, i + 1
+          ));
+        } else if aws.is_empty() {
+          return Err(format!(
+            "feedback {} ({}) to {} fits none of board {}'s {} answers left",
+            turn, fbs[i], gw, i + 1, alens[i]
+          ));
+        }
+      }
+    } else {
+      w = Some(gw);
+    }
+  }
+  Ok((state, w, turn))
+}
+
+#[cfg(feature = "solve")]
+fn open_book(book: Option<String>) -> Book {
+  let bkp = book.map(PathBuf::from)
+    .or_else(default_book_path)
+    .unwrap_or_else(|| exit_with("couldn't find book path"));
+  Book::load(&bkp)
+    .unwrap_or_else(|err| exit_with(&format!("couldn't load book {}: {}", bkp.display(), err)))
+}
+
 fn main() {
   let cli = cli_parse();
 
@@ -60,94 +268,596 @@ fn main() {
       ntops2,
       turns,
       ecut,
+      exact,
+      lbprune,
+      objective,
+      time_limit,
+      book,
+      nobook,
+      savebook,
+      progress,
+      stats,
+      adversarial,
+      dirty,
+      format,
+      tree,
     } => {
+      let json = match format.as_str() {
+        "text" => false,
+        "json" => true,
+        _ => exit_with("format should be text or json"),
+      };
+      JSON_ERRORS.store(json, Ordering::Relaxed);
+      if adversarial && tree.is_some() {
+        exit_with("a tree can't be played against the adversary");
+      }
+
       // create state + sdata
-      let (gwb, awb) = WBank::from2(wbp, wlen).unwrap();
-      let adata = AData::load(&hdp, &ldp).unwrap();
+      let (gwb, awb) = load_banks(&wbp, wlen);
+      let bank = bank_key(&gwb, &awb);
+      let adata = AData::load(&hdp, &ldp)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load solver data: {}", err)));
+      let data = data_key(&hdp, &ldp)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load solver data: {}", err)));
       let cache = Cache::new(64, 16);
-      let mut state = State::new2(gwb.data, awb.data, wlen.into(), turns, hard);
       let mut sd = SData::new(adata, cache, ntops1, ntops2, ecut);
+      sd.weights = awb.weight_map();
+      let state = State::new2(gwb.data, awb.data, wlen.into(), turns, hard);
+      sd.exact = exact;
+      sd.lbprune = lbprune;
+      sd.obj = Objective::parse(&objective, turns)
+        .unwrap_or_else(|| exit_with(&format!("invalid objective {}", objective)));
+      let (state, w, turn) = follow_gamestate(state, &gamestate, dirty)
+        .unwrap_or_else(|err| exit_with(&err));
 
-      // parse gamestate
-      let mut w: Option<Word> = None;
-      let mut turn = 0u32;
-      let mut it = gamestate.split('.');
-      while let Some(s_a) = it.next() {
-        if s_a.is_empty() {
-          break;
+      // follow the given tree
+      let treed = tree.as_ref().map(|tree| {
+        let root = DTree::load(tree)
+          .unwrap_or_else(|err| exit_with(&format!("couldn't load tree {}: {}", tree, err)));
+        let (dtree, _) = walk_gamestate(&root, &gamestate)
+          .unwrap_or_else(|err| exit_with(&err));
+        let mut aws1 = dtree.answers();
+        let mut aws2 = state.answers();
+        aws1.sort();
+        aws2.sort();
+        if aws1 != aws2 {
+          exit_with(&format!("the tree finds {} answers, not the {} left", aws1.len(), aws2.len()));
         }
-        turn += 1;
-        if let Some(s_b) = it.next() {
-          let gw = Word::from_str(s_a).unwrap();
-          let fb = Feedback::from_str(s_b).unwrap();
-          state = state.fb_follow(gw, fb);
-        } else {
-          w = Some(Word::from_str(s_a).unwrap());
-        }
-      }
+        dtree.clone()
+      });
+
+      // check book
+      let from_tree = treed.is_some();
+      let mut book = if nobook || adversarial || from_tree { None } else { Some(open_book(book)) };
+      let key = BookKey::new(bank, data, wlen, turns, hard, &sd, &gamestate);
+      let booked = book.as_ref()
+        .filter(|_| !elist)
+        .and_then(|book| book.get(&key))
+        .cloned();
 
       // list answers
-      if alist {
+      let wsum = sd.wsum(&state);
+      if alist && !json {
         println!("Potential Answers:");
-        for (i, aw) in state.aws.iter().enumerate() {
-          println!("{}. {}", i + 1, aw);
+        for (i, aw) in state.answers().iter().enumerate() {
+          if sd.weights.is_some() {
+            println!("{}. {} ({:.3})", i + 1, aw, sd.weight(aw) as f64 / wsum as f64);
+          } else {
+            println!("{}. {}", i + 1, aw);
+          }
         }
         println!();
       }
 
       // solve + elist?
       let inst = Instant::now();
+      sd.deadline = time_limit.map(|secs| inst + Duration::from_secs_f64(secs));
+      sd.stats = Arc::new(Stats::new());
+      if progress && !elist {
+        sd.progress = Some(state.n);
+      }
       let given = w.is_some();
-      let dtree = if !given && elist {
-        let ws = state.top_words(&sd);
-        let mut scores: Vec<(Word, DTree)> = ws
+      let from_book = booked.is_some();
+      let mut evals: Vec<(Word, DTree)> = Vec::new();
+      let dtree = if let Some(dt) = booked {
+        Some(dt)
+      } else if let Some(dt) = treed {
+        Some(dt)
+      } else if adversarial && !given && elist {
+        evals = state.adversary_words(&sd)
           .iter()
-          .filter_map(|w| Some((*w, state.solve_given(*w, &sd, u32::MAX)?)))
+          .filter_map(|w| Some((*w, state.solve_adversarial_given(*w, &sd, u32::MAX)?)))
           .collect();
-        scores.sort_by_key(|(_w, dt)| dt.get_tot());
-        println!("Evaluations:");
-        for (i, (w, dt)) in scores.iter().enumerate() {
-          println!(
-            "{}. {}: {}/{} = {:.3}",
-            i + 1,
-            w.to_string(),
-            dt.get_tot(),
-            state.aws.len(),
-            dt.get_tot() as f64 / state.aws.len() as f64
-          );
+        evals.sort_by_key(|(_w, dt)| dt.get_tot());
+        evals.first().map(|(_w, dt)| dt.clone())
+      } else if adversarial {
+        match w {
+          Some(w) => state.solve_adversarial_given(w, &sd, u32::MAX),
+          None => state.solve_adversarial(&sd, u32::MAX),
         }
-        println!();
-        Some(scores.remove(0).1)
+      } else if time_limit.is_some() && !elist {
+        state.solve_anytime(w, &sd)
+      } else if !given && elist {
+        evals = state.top_words(&sd)
+          .iter()
+          .filter_map(|w| Some((*w, state.solve_given(*w, &sd, u32::MAX)?)))
+          .collect();
+        evals.sort_by_key(|(_w, dt)| sd.obj.cost(dt, state.n));
+        evals.first().map(|(_w, dt)| dt.clone())
       } else if !given {
         state.solve(&sd, u32::MAX)
       } else {
         state.solve_given(w.unwrap(), &sd, u32::MAX)
+      };
+
+      // list evaluations
+      let elisted = elist && !given && !from_tree;
+      if elisted && !json {
+        println!("Evaluations:");
+        for (i, (w, dt)) in evals.iter().enumerate() {
+          if adversarial {
+            println!("{}. {}: {} guesses", i + 1, w.to_string(), dt.get_tot());
+          } else {
+            println!(
+              "{}. {}: {}/{} = {:.3}",
+              i + 1,
+              w.to_string(),
+              dt.get_tot(),
+              wsum,
+              dt.get_tot() as f64 / wsum as f64
+            );
+          }
+        }
+        println!();
+      }
+      let dtree = dtree.unwrap_or_else(|| exit_with(&format!("couldn't solve within {} turns", turns)));
+
+      // add to book if asked and not cut short
+      let cancelled = sd.cancelled();
+      if let Some(ref mut book) = book.as_mut().filter(|_| savebook && !cancelled) {
+        if book.get(&key).is_none() {
+          book.insert(key, dtree.clone());
+          book.save()
+            .unwrap_or_else(|err| exit_with(&format!("couldn't save book: {}", err)));
+        }
       }
-      .expect("couldn't make dtree!");
 
       // print results
-      if let DTree::Node {
-        tot,
-        word,
-        fbmap: _,
-      } = dtree
-      {
+      let time = inst.elapsed().as_millis() as f64 / 1000.;
+      let proven = exact && sd.obj.decomposes() && !cancelled && !from_tree;
+      if json {
+        let mut js = json!({
+          "gamestate": gamestate,
+          "turn": turn,
+          "params": {
+            "wbp": wbp,
+            "hdp": hdp,
+            "ldp": ldp,
+            "wlen": wlen,
+            "hard": hard,
+            "turns": turns,
+            "ntops1": ntops1,
+            "ntops2": ntops2,
+            "ecut": ecut,
+            "exact": exact,
+            "lbprune": lbprune,
+            "objective": sd.obj.to_string(),
+            "time_limit": time_limit,
+            "adversarial": adversarial,
+            "dirty": dirty,
+            "book": book.is_some(),
+            "savebook": savebook,
+            "tree": tree,
+          },
+        });
+        if alist {
+          js["answers"] = state.answers().iter().map(|aw| json!({
+            "word": aw.to_string(),
+            "prob": sd.weight(aw) as f64 / wsum as f64,
+          })).collect();
+        }
+        if elisted {
+          js["evaluations"] = evals.iter().map(|(w, dt)| if adversarial {
+            json!({"word": w.to_string(), "guesses": dt.get_tot()})
+          } else {
+            json!({
+              "word": w.to_string(),
+              "tot": dt.get_tot(),
+              "avg": dt.get_tot() as f64 / wsum as f64,
+            })
+          }).collect();
+        }
+        let word = match dtree {
+          DTree::Node { word, .. } => Some(word.to_string()),
+          DTree::Leaf => None,
+        };
+        js["solution"] = if adversarial {
+          json!({
+            "word": word,
+            "guesses": dtree.get_tot(),
+            "line": dtree_line(&dtree),
+            "time_limit_reached": cancelled,
+          })
+        } else {
+          json!({
+            "word": word,
+            "tot": dtree.get_tot(),
+            "avg": dtree.get_tot() as f64 / wsum as f64,
+            "hist": dtree.get_hist(),
+            "cost": sd.obj.cost(&dtree, state.n),
+            "booked": from_book,
+            "from_tree": from_tree,
+            "proven_optimal": proven,
+            "time_limit_reached": cancelled,
+          })
+        };
+        js["time"] = time.into();
+        if stats {
+          js["stats"] = sd.stats.to_json();
+        }
+        println!("{}", js);
+      } else if let (true, DTree::Node { tot, word, .. }) = (adversarial, &dtree) {
         println!("Solution:");
         println!(
-          "{}: {}/{} = {:.3} in {:.3}s",
+          "{}: {} guesses against the adversary in {:.3}s{}",
           word.to_string(),
           tot,
-          state.aws.len(),
-          tot as f64 / state.aws.len() as f64,
+          time,
+          if cancelled { " (time limit reached)" } else { "" }
+        );
+        println!("{}", dtree_line(&dtree));
+      } else if let DTree::Node { tot, word, .. } = dtree {
+        println!("Solution:");
+        println!(
+          "{}: {}/{} = {:.3} in {:.3}s{}",
+          word.to_string(),
+          tot,
+          wsum,
+          tot as f64 / wsum as f64,
+          time,
+          if cancelled {
+            " (time limit reached)"
+          } else if from_tree {
+            " (from tree)"
+          } else if proven {
+            " (proven optimal)"
+          } else {
+            ""
+          }
+        );
+        if !sd.obj.prunes() {
+          let hist = dtree.get_hist().iter()
+            .enumerate()
+            .map(|(i, x)| format!("{}: {}", i as u32 + turn + 1, x))
+            .collect::<Vec<String>>()
+            .join(", ");
+          println!("{} = {:?} ({})", sd.obj, sd.obj.cost(&dtree, state.n), hist);
+        }
+      }
+      if stats && !json {
+        println!();
+        println!("Statistics:");
+        println!("{}", sd.stats);
+      }
+
+      // output dtree
+      if let Some(dt) = dt {
+        dtree.save(&dt)
+          .unwrap_or_else(|err| exit_with(&format!("couldn't save tree {}: {}", dt, err)));
+      }
+    }
+    #[cfg(feature = "solve")]
+    Commands::Msolve {
+      gamestate,
+      boards,
+      hard,
+      hrule,
+      elist,
+      wlen,
+      wbp,
+      hdp,
+      ldp,
+      ntops,
+      nsamples,
+      exhaust,
+      seed,
+      turns,
+      dirty,
+    } => {
+      // create state + mdata
+      let (gwb, awb) = load_banks(&wbp, wlen);
+      let adata = AData::load(&hdp, &ldp)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load solver data: {}", err)));
+      let cache = MCache::new(64, 16);
+      let mut md = MData::new(adata, cache, ntops, nsamples, 15);
+      md.exhaust = exhaust;
+      md.seed = seed;
+      let turns = turns.unwrap_or(boards + NEXTRA as u32);
+      let finished = vec![false; boards as usize];
+      let awss = vec![awb.data; boards as usize];
+      let mut root = MState::new2(gwb.data, awss, wlen.into(), boards, finished, turns, hard);
+      root.hrule = HardRule::from_str(&hrule)
+        .unwrap_or_else(|| exit_with(&format!("invalid hard mode rule {}", hrule)));
+      let (state, w, _) = follow_mgamestate(root, &gamestate, dirty)
+        .unwrap_or_else(|err| exit_with(&err));
+
+      // list each board's answers
+      println!("Potential Answers:");
+      for (i, (aws, fin)) in zip(&state.awss, &state.finished).enumerate() {
+        if *fin {
+          println!("{}. solved", i + 1);
+        } else {
+          let aws: Vec<String> = aws.iter()
+            .map(|ai| state.fbt.aws[*ai as usize].to_string())
+            .collect();
+          println!("{}. ({}) {}", i + 1, aws.len(), aws.join(", "));
+        }
+      }
+      println!();
+      if state.finished.iter().all(|&fin| fin) {
+        return;
+      }
+      if let Some(i) = state.awss.iter().position(|aws| aws.is_empty()) {
+        exit_with(&format!("board {} has no potential answers", i + 1));
+      }
+
+      // solve + elist?
+      let inst = Instant::now();
+      let given = w.is_some();
+      let mtree = if !given && elist {
+        let ws = state.top_words(&md);
+        let mut scores: Vec<MTree> = ws
+          .iter()
+          .filter_map(|w| state.solve_given(*w, &mut md))
+          .collect();
+        scores.sort_by(|mt1, mt2| mt1.get_eval().partial_cmp(&mt2.get_eval()).unwrap());
+        println!("Evaluations:");
+        for (i, mt) in scores.iter().enumerate() {
+          println!("{}. {}: {:.3}", i + 1, mt.get_word().unwrap().to_string(), mt.get_eval());
+        }
+        println!();
+        if scores.is_empty() { None } else { Some(scores.remove(0)) }
+      } else if !given {
+        state.solve(&mut md)
+      } else {
+        state.solve_given(w.unwrap(), &mut md)
+      }
+      .unwrap_or_else(|| exit_with(&format!("couldn't solve within {} turns", turns)));
+
+      // print results
+      if let MTree::Node { eval, word, .. } = mtree {
+        println!("Solution:");
+        println!(
+          "{}: {:.3} expected guesses left in {:.3}s",
+          word.to_string(),
+          eval,
           inst.elapsed().as_millis() as f64 / 1000.
         );
-        // output dtree
-        if let Some(dt) = dt {
-          let mut f = File::create(dt).unwrap();
-          dtree.pprint(&mut f, &"".into(), turn);
+      }
+    }
+    #[cfg(feature = "solve")]
+    Commands::Book { command: BookCommands::Build {
+      gamestates,
+      book,
+      wlen,
+      wbp,
+      hdp,
+      ldp,
+      hard,
+      ntops1,
+      ntops2,
+      turns,
+      ecut,
+      exact,
+      objective,
+    }} => {
+      let (gwb, awb) = load_banks(&wbp, wlen);
+      let bank = bank_key(&gwb, &awb);
+      let data = data_key(&hdp, &ldp).unwrap();
+      let adata = AData::load(&hdp, &ldp).unwrap();
+      let cache = Cache::new(64, 16);
+      let mut sd = SData::new(adata, cache, ntops1, ntops2, ecut);
+      sd.weights = awb.weight_map();
+      let root = State::new2(gwb.data, awb.data, wlen.into(), turns, hard);
+      sd.exact = exact;
+      sd.obj = Objective::parse(&objective, turns).expect("invalid objective!");
+      let mut book = open_book(book);
+
+      for gamestate in gamestates {
+        let inst = Instant::now();
+        let (state, w, _) = match follow_gamestate(root.clone(), &gamestate, false) {
+          Ok(res) => res,
+          Err(err) => {
+            println!("\"{}\": {}", gamestate, err);
+            continue;
+          }
+        };
+        let dtree = match w {
+          Some(w) => state.solve_given(w, &sd, u32::MAX),
+          None => state.solve(&sd, u32::MAX),
+        };
+        if let Some(dtree) = dtree {
+          println!(
+            "\"{}\": {}/{} in {:.3}s",
+            gamestate,
+            dtree.get_tot(),
+            sd.wsum(&state),
+            inst.elapsed().as_millis() as f64 / 1000.
+          );
+          let key = BookKey::new(bank, data, wlen, turns, hard, &sd, &gamestate);
+          book.insert(key, dtree);
+          book.save().expect("couldn't save book!");
+        } else {
+          println!("\"{}\": couldn't make dtree!", gamestate);
+        }
+      }
+    }
+    #[cfg(feature = "solve")]
+    Commands::Book { command: BookCommands::List { book } } => {
+      let book = open_book(book);
+      let mut items: Vec<(&BookKey, &DTree)> = book.iter().collect();
+      items.sort_by_key(|(key, _)| &key.gamestate);
+      println!("{} ({} entries):", book.path().display(), book.len());
+      for (i, (key, dt)) in items.iter().enumerate() {
+        let word = match dt {
+          DTree::Node { word, .. } => word.to_string(),
+          DTree::Leaf => String::new(),
+        };
+        println!(
+          "{}. \"{}\": {} {} (bank={:016x}, data={:016x}, wlen={}, turns={}, mode={}, ntops1={}, ntops2={}, ecut={}, exact={}, lbprune={}, objective={})",
+          i + 1,
+          key.gamestate,
+          word,
+          dt.get_tot(),
+          key.bank,
+          key.data,
+          key.wlen,
+          key.turns,
+          if key.hard { "H" } else { "E" },
+          key.ntops1,
+          key.ntops2,
+          key.ecut,
+          key.exact,
+          key.lbprune,
+          key.objective,
+        );
+      }
+    }
+    #[cfg(feature = "solve")]
+    Commands::Book { command: BookCommands::Clear { gamestates, book } } => {
+      let mut book = open_book(book);
+      let gamestates: Vec<String> = gamestates.iter()
+        .map(|gs| gs.trim_matches('.').to_ascii_lowercase())
+        .collect();
+      let n = book.remove_if(|key| gamestates.is_empty() || gamestates.contains(&key.gamestate));
+      book.save().expect("couldn't save book!");
+      println!("removed {} entries", n);
+    }
+    #[cfg(feature = "solve")]
+    Commands::Tree { command: TreeCommands::Walk { tree, gamestate, alist, dt } } => {
+      let root = DTree::load(&tree)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load tree: {}", err)));
+      let (dtree, turn) = walk_gamestate(&root, &gamestate)
+        .unwrap_or_else(|err| exit_with(&err));
+
+      // list answers
+      if alist {
+        println!("Potential Answers:");
+        for (i, aw) in dtree.answers().iter().enumerate() {
+          println!("{}. {}", i + 1, aw);
         }
+        println!();
+      }
+
+      // print next guess
+      if let DTree::Node { tot, word, .. } = dtree {
+        let n: u32 = dtree.get_hist().iter().sum();
+        let hist = dtree.get_hist().iter()
+          .enumerate()
+          .map(|(i, x)| format!("{}: {}", i as u32 + turn + 1, x))
+          .collect::<Vec<String>>()
+          .join(", ");
+        println!("Next:");
+        println!("{}: {}/{} = {:.3} ({})", word, tot, n, *tot as f64 / n as f64, hist);
+      } else {
+        println!("Solved in {} guesses", turn);
+      }
+
+      // output subtree
+      if let Some(dt) = dt {
+        dtree.save(&dt)
+          .unwrap_or_else(|err| exit_with(&format!("couldn't save tree {}: {}", dt, err)));
       }
     }
+    #[cfg(feature = "solve")]
+    Commands::Tree { command: TreeCommands::Verify { tree, wlen, wbp, turns, hard } } => {
+      let dtree = DTree::load(&tree)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load tree: {}", err)));
+      let (_gwb, awb) = load_banks(&wbp, wlen);
+      let errs = dtree.verify(&awb.data, turns, hard);
+      for err in &errs {
+        println!("{}", err);
+      }
+      if !errs.is_empty() {
+        exit_with(&format!("found {} problems with the tree", errs.len()));
+      }
+      println!(
+        "OK: solves all {} answers within {} turns{}",
+        awb.data.len(),
+        turns,
+        if hard { " in hard mode" } else { "" }
+      );
+    }
+    #[cfg(feature = "solve")]
+    Commands::Tree { command: TreeCommands::Stats { tree } } => {
+      let dtree = DTree::load(&tree)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load tree: {}", err)));
+      if let DTree::Node { tot, word, .. } = &dtree {
+        let n: u32 = dtree.get_hist().iter().sum();
+        let hist = dtree.get_hist().iter()
+          .enumerate()
+          .map(|(i, x)| format!("{}: {}", i + 1, x))
+          .collect::<Vec<String>>()
+          .join(", ");
+        println!("{}: {}/{} = {:.3}", word, tot, n, *tot as f64 / n as f64);
+        println!("Guesses per answer: {}", hist);
+        println!("Worst case: {} guesses", dtree.depth());
+        println!("Distinct words: {}", dtree.words().len());
+      } else {
+        println!("The tree is empty");
+      }
+    }
+    #[cfg(feature = "solve")]
+    Commands::Tree { command: TreeCommands::Trace { tree, answers, wlen, wbp, top } } => {
+      let dtree = DTree::load(&tree)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load tree: {}", err)));
+      let bank = answers.is_empty();
+      let aws: Vec<Word> = if bank {
+        load_banks(&wbp, wlen).1.data
+      } else {
+        answers.iter()
+          .map(|s| Word::from_str(s)
+            .unwrap_or_else(|| exit_with(&format!("answer {} should only have letters", s))))
+          .collect()
+      };
+      let mut traces: Vec<_> = aws.iter()
+        .map(|aw| (*aw, dtree.trace(*aw)))
+        .collect();
+
+      // the answers taking the most turns, any the tree doesn't find first
+      if bank {
+        traces.sort_by_key(|(aw, line)| {
+          (std::cmp::Reverse(line.as_ref().map_or(usize::MAX, |line| line.len())), *aw)
+        });
+        traces.truncate(top);
+        println!("Most turns:");
+      }
+      for (i, (aw, line)) in traces.iter().enumerate() {
+        let num = if bank { format!("{}. ", i + 1) } else { String::new() };
+        match line {
+          Some(line) => println!(
+            "{}{}: {} ({} guesses)",
+            num,
+            aw,
+            line.iter().map(|(gw, fb)| format!("{}.{}", gw, fb)).collect::<Vec<String>>().join("."),
+            line.len()
+          ),
+          None => println!("{}{}: not found by the tree", num, aw),
+        }
+      }
+    }
+    #[cfg(feature = "solve")]
+    Commands::Tree { command: TreeCommands::Convert { tree, out, format } } => {
+      let fmt = match format {
+        Some(format) => TreeFormat::from_str(&format)
+          .unwrap_or_else(|| exit_with("format should be text, json or selby")),
+        None => TreeFormat::from_path(&out),
+      };
+      let dtree = DTree::load(&tree)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't load tree: {}", err)));
+      dtree.save_as(&out, fmt)
+        .unwrap_or_else(|err| exit_with(&format!("couldn't save tree {}: {}", out, err)));
+    }
     #[cfg(feature = "gen")]
     Commands::Hgen {
       niter,
@@ -159,9 +869,10 @@ fn main() {
       ntops1,
       ntops2,
       turns,
+      hard,
       ecut,
     } => {
-      let (gwb, awb) = WBank::from2(DEFWBP, NLETS as u8).unwrap();
+      let (gwb, awb) = load_banks(&wbp, wlen);
       let adata = AData::load(&hdp, &ldp).unwrap();
       let cache = Cache::new(64, 16);
       let alen_max = awb.len();
@@ -177,6 +888,7 @@ fn main() {
         ntops1: Range::new(ntops1, ntops1, true),
         ntops2: Range::new(ntops2, ntops2, true),
         ecuts: Range::new(ecut, ecut, true),
+        hard,
         niter,
       };
       hgen.run(Path::new(&out));
@@ -193,9 +905,10 @@ fn main() {
       ntops1,
       ntops2,
       turns,
+      hard,
       ecut,
     } => {
-      let (gwb, awb) = WBank::from2(DEFWBP, NLETS as u8).unwrap();
+      let (gwb, awb) = load_banks(&wbp, wlen);
       let adata = AData::load(&hdp, &ldp).unwrap();
       let cache = Cache::new(64, 16);
 
@@ -211,6 +924,7 @@ fn main() {
         ntops1,
         ntops2,
         ecuts: ecut,
+        hard,
         niter,
       };
       ggen.run(Path::new(&out));
@@ -230,7 +944,7 @@ fn main() {
       turns,
       ecut,
     } => {
-      let (gwb, awb) = WBank::from2(DEFWBP, NLETS as u8).unwrap();
+      let (gwb, awb) = load_banks(&wbp, wlen);
       let adata = AData::load(&hdp, &ldp).unwrap();
       let cache = Cache::new(64, 16);
 
@@ -251,5 +965,43 @@ fn main() {
       };
       lgen.run(Path::new(&out));
     }
+    #[cfg(feature = "gen")]
+    Commands::Mgen {
+      niter,
+      out,
+      wlen,
+      wbp,
+      hdp,
+      ldp,
+      boards,
+      alens,
+      extras,
+      unshared,
+      risk,
+      ntops,
+      nsamples,
+      hard,
+    } => {
+      let (gwb, awb) = load_banks(&wbp, wlen);
+      let adata = AData::load(&hdp, &ldp).unwrap();
+
+      let mut mgen = MGen {
+        gwb,
+        awb,
+        wlen: wlen as u32,
+        adata,
+        boards,
+        alens,
+        extras,
+        unshared,
+        risk,
+        ntops,
+        nsamples,
+        hard,
+        niter,
+      };
+      mgen.run(Path::new(&out))
+        .unwrap_or_else(|err| exit_with(&format!("couldn't add to {}: {}", out, err)));
+    }
   }
 }
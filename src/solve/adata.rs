@@ -39,8 +39,15 @@ impl AData {
     Ok(Self {approxs, lbounds})
   }
 
+  /// approximate total for n answers, extrapolated past the data assuming
+  /// the average grows logarithmically
   #[inline]
   pub fn get_approx(&self, n: usize) -> Option<f64> {
+    let m = self.approxs.len();
+    if n > m && m > 1 {
+      let avg = self.approxs[m-1] / m as f64;
+      return Some(n as f64 * avg * (n as f64).ln() / (m as f64).ln());
+    }
     self.approxs.get(n-1).map(|x| *x)
   }
 
@@ -0,0 +1,125 @@
+use rayon::prelude::*;
+
+use super::state::{SData, State};
+use crate::ds::*;
+
+// playing against an absurdle-like adversary, which answers each guess with
+// the feedback keeping the most answers (see adversary_fb), so a strategy is a
+// single line of guesses and its length is the number of guesses it takes
+impl State {
+  /// the adversary's feedback to a guess, and the position it leaves
+  pub fn adversary_follow(&self, gw: &Word) -> (Feedback, State) {
+    let fbp = self.fb_partition(gw);
+    let fb = adversary_fb(fbp.iter().map(|(fb, s2)| (*fb, s2.aws.len()))).unwrap();
+    fbp.into_iter().find(|(fb2, _)| *fb2 == fb).unwrap()
+  }
+
+  /// guesses leaving the adversary the fewest answers, best heuristic first
+  /// (every such guess if exact, otherwise the top ntops2)
+  pub fn adversary_words(&self, sd: &SData) -> Vec<Word> {
+    let alen = self.aws.len() as u32;
+    let mut tups: Vec<(Word, u32, f64)> = self
+      .guesses()
+      .into_par_iter()
+      .map(|gw| {
+        let counts = self.fb_counts(&gw);
+        let most = *counts.iter().max().unwrap();
+        (gw, most, self.heuristic(&gw, sd))
+      })
+      .filter(|(_, most, _)| *most < alen)
+      .collect();
+    tups.sort_by(|(_, m1, f1), (_, m2, f2)| m1.cmp(m2).then(f1.partial_cmp(f2).unwrap()));
+    let ntops = if sd.exact { tups.len() } else { sd.ntops2 as usize };
+    tups.iter().take(ntops).map(|(gw, _, _)| *gw).collect()
+  }
+
+  /// line of guesses against the adversary, starting with gw, if it takes
+  /// fewer than beta guesses
+  pub fn solve_adversarial_given(&self, gw: Word, sd: &SData, beta: u32) -> Option<DTree> {
+    if self.n == 0 || beta <= 1 || sd.cancelled() {
+      return None;
+    }
+    let (fb, s2) = self.adversary_follow(&gw);
+    if fb.is_correct() {
+      return Some(DTree::node(gw, [(fb, DTree::Leaf)].into(), 1));
+    }
+    let dt = s2.solve_adversarial(sd, beta - 1)?;
+    Some(DTree::node(gw, [(fb, dt)].into(), 1))
+  }
+
+  /// shortest line of guesses found against the adversary, if it takes fewer
+  /// than beta guesses
+  pub fn solve_adversarial(&self, sd: &SData, beta: u32) -> Option<DTree> {
+    // one answer -> guess it
+    if self.aws.len() == 1 {
+      let aw = self.fbt.aws[self.aws.first().unwrap() as usize];
+      return self.solve_adversarial_given(aw, sd, beta);
+    }
+    // the adversary never gives the answer away in one guess
+    if self.n < 2 || beta <= 2 {
+      return None;
+    }
+
+    let mut best = None;
+    let mut beta = beta;
+    for gw in self.adversary_words(sd) {
+      if let Some(dt) = self.solve_adversarial_given(gw, sd, beta) {
+        beta = dt.get_tot();
+        best = Some(dt);
+        if beta == 2 {
+          break;
+        }
+      }
+    }
+    best
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn adversary_choice() {
+    let fb = |s| Feedback::from_str(s).unwrap();
+    // most answers, then fewest greens, then fewest yellows
+    let counts = vec![(fb("bbbbb"), 3), (fb("gbbbb"), 5), (fb("ybbbb"), 5)];
+    assert_eq!(adversary_fb(counts), Some(fb("ybbbb")));
+    let counts = vec![(fb("gybbb"), 2), (fb("ggbbb"), 2), (fb("gyybb"), 2)];
+    assert_eq!(adversary_fb(counts), Some(fb("gybbb")));
+    assert_eq!(adversary_fb(vec![]), None);
+
+    // the adversary keeps the largest class
+    let state = State::new3();
+    let gw = Word::from_str("salet").unwrap();
+    let (_, s2) = state.adversary_follow(&gw);
+    let most = *state.fb_counts(&gw).iter().max().unwrap();
+    assert_eq!(s2.aws.len() as u32, most);
+    assert_eq!(s2.n, state.n - 1);
+  }
+
+  #[test]
+  fn adversarial_solve() {
+    let sd = SData::new2(2, 5);
+    for _ in 0..5 {
+      let state = State::random(30);
+      let dt = state.solve_adversarial(&sd, u32::MAX).unwrap();
+      // the line replays against the adversary
+      let mut s = state.clone();
+      let mut node = &dt;
+      let mut nguesses = 0;
+      while let DTree::Node { word, fbmap, .. } = node {
+        let (fb, s2) = s.adversary_follow(word);
+        node = fbmap.get(&fb).unwrap();
+        s = s2;
+        nguesses += 1;
+      }
+      assert!(s.aws.len() == 1);
+      assert_eq!(dt.get_tot(), nguesses);
+      assert!(nguesses <= state.n);
+
+      // and nothing shorter passes beta
+      assert!(state.solve_adversarial(&sd, nguesses).is_none());
+    }
+  }
+}
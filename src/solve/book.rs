@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Result, Write};
+use std::path::{Path, PathBuf};
+
+use super::state::SData;
+use crate::ds::*;
+
+/// everything a book entry's solution depends on
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BookKey {
+  /// fingerprint of the guess and answer banks
+  pub bank: u64,
+  /// fingerprint of the heuristic and lower bounds data
+  pub data: u64,
+  pub wlen: u8,
+  pub turns: u32,
+  pub hard: bool,
+  pub ntops1: u32,
+  pub ntops2: u32,
+  pub ecut: u32,
+  pub exact: bool,
+  pub lbprune: bool,
+  pub objective: String,
+  /// normalized gamestate
+  pub gamestate: String,
+}
+
+/// persistent solutions for gamestates (an "opening book")
+#[derive(Debug, Clone)]
+pub struct Book {
+  path: PathBuf,
+  entries: HashMap<BookKey, DTree>,
+}
+
+/// fingerprint the banks and weights (stable across runs unlike DefaultHasher)
+pub fn bank_key(gwb: &WBank, awb: &WBank) -> u64 {
+  // fnv-1a over letters, with a separator after each word and bank
+  let mut h = 0xcbf29ce484222325u64;
+  let mut feed = |x: u8| {
+    h ^= x as u64;
+    h = h.wrapping_mul(0x100000001b3);
+  };
+  for wb in [gwb, awb] {
+    for w in &wb.data {
+      w.data[0..w.wlen as usize].iter().for_each(|x| feed(*x));
+      feed(254);
+    }
+    feed(255);
+  }
+  for x in &awb.weights {
+    x.to_le_bytes().iter().for_each(|x| feed(*x));
+  }
+  h
+}
+
+/// fingerprint the heuristic and lower bounds data files' contents
+pub fn data_key<P>(hdp: &P, ldp: &P) -> Result<u64>
+where P: AsRef<Path> + ?Sized, {
+  // fnv-1a over bytes, with each file's length after it
+  let mut h = 0xcbf29ce484222325u64;
+  let mut feed = |x: u8| {
+    h ^= x as u64;
+    h = h.wrapping_mul(0x100000001b3);
+  };
+  for p in [hdp, ldp] {
+    let bytes = fs::read(p)?;
+    bytes.iter().for_each(|x| feed(*x));
+    (bytes.len() as u64).to_le_bytes().iter().for_each(|x| feed(*x));
+  }
+  Ok(h)
+}
+
+/// $XDG_CACHE_HOME/hustle/book.csv or $HOME/.cache/hustle/book.csv
+pub fn default_book_path() -> Option<PathBuf> {
+  let mut pb = PathBuf::new();
+  if let Ok(xdgp) = env::var("XDG_CACHE_HOME") {
+    pb.push(xdgp);
+  } else {
+    pb.push(env::var("HOME").ok()?);
+    pb.push(".cache");
+  }
+  pb.push("hustle/book.csv");
+  Some(pb)
+}
+
+impl BookKey {
+  pub fn new(bank: u64, data: u64, wlen: u8, turns: u32, hard: bool,
+             sd: &SData, gamestate: &str) -> Self {
+    Self {
+      bank,
+      data,
+      wlen,
+      turns,
+      hard,
+      ntops1: sd.ntops1,
+      ntops2: sd.ntops2,
+      ecut: sd.ecut,
+      exact: sd.exact,
+      lbprune: sd.lbprune,
+      objective: sd.obj.to_string(),
+      gamestate: gamestate.trim_matches('.').to_ascii_lowercase(),
+    }
+  }
+}
+
+impl Book {
+  fn header() -> &'static str {
+    "bank,data,wlen,turns,mode,ntops1,ntops2,ecut,exact,lbprune,objective,gamestate,dtree"
+  }
+
+  /// load book, empty if the file doesn't exist yet (or is an older format)
+  pub fn load<P>(path: P) -> Result<Self>
+  where P: AsRef<Path>, {
+    let path = path.as_ref().to_path_buf();
+    let mut entries = HashMap::new();
+    if path.exists() {
+      let mut lines = BufReader::new(File::open(&path)?).lines();
+      if lines.next().transpose()?.as_deref() != Some(Self::header()) {
+        return Ok(Self { path, entries });
+      }
+      for line in lines {
+        if let Some((key, dt)) = Self::parse_line(&line?) {
+          entries.insert(key, dt);
+        }
+      }
+    }
+    Ok(Self { path, entries })
+  }
+
+  fn parse_line(line: &str) -> Option<(BookKey, DTree)> {
+    // objective is the only field with commas
+    let vec: Vec<&str> = line.split(',').collect();
+    if vec.len() < 13 {
+      return None;
+    }
+    let key = BookKey {
+      bank: u64::from_str_radix(vec[0], 16).ok()?,
+      data: u64::from_str_radix(vec[1], 16).ok()?,
+      wlen: vec[2].parse().ok()?,
+      turns: vec[3].parse().ok()?,
+      hard: vec[4] == "H",
+      ntops1: vec[5].parse().ok()?,
+      ntops2: vec[6].parse().ok()?,
+      ecut: vec[7].parse().ok()?,
+      exact: vec[8].parse().ok()?,
+      lbprune: vec[9].parse().ok()?,
+      objective: vec[10..vec.len() - 2].join(","),
+      gamestate: vec[vec.len() - 2].to_owned(),
+    };
+    Some((key, DTree::from_compact(vec[vec.len() - 1])?))
+  }
+
+  pub fn save(&self) -> Result<()> {
+    if let Some(dir) = self.path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    let mut f = File::create(&self.path)?;
+    writeln!(f, "{}", Self::header())?;
+    let mut items: Vec<(&BookKey, &DTree)> = self.entries.iter().collect();
+    items.sort_by_key(|(key, _)| &key.gamestate);
+    for (key, dt) in items {
+      writeln!(
+        f,
+        "{:016x},{:016x},{},{},{},{},{},{},{},{},{},{},{}",
+        key.bank,
+        key.data,
+        key.wlen,
+        key.turns,
+        if key.hard { "H" } else { "E" },
+        key.ntops1,
+        key.ntops2,
+        key.ecut,
+        key.exact,
+        key.lbprune,
+        key.objective,
+        key.gamestate,
+        dt.to_compact(),
+      )?;
+    }
+    Ok(())
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn get(&self, key: &BookKey) -> Option<&DTree> {
+    self.entries.get(key)
+  }
+
+  pub fn insert(&mut self, key: BookKey, dt: DTree) {
+    self.entries.insert(key, dt);
+  }
+
+  /// remove entries, returning how many were removed
+  pub fn remove_if<F>(&mut self, f: F) -> usize
+  where F: Fn(&BookKey) -> bool, {
+    let len = self.entries.len();
+    self.entries.retain(|key, _| !f(key));
+    len - self.entries.len()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&BookKey, &DTree)> {
+    self.entries.iter()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::solve::State;
+
+  #[test]
+  fn save_load() {
+    let sd = SData::new2(2, 200);
+    let (gwb, awb) = WBank::from2(DEFWBP, 5).unwrap();
+    let state = State::random(20);
+    let dt = state.solve(&sd, u32::MAX).unwrap();
+    assert_eq!(DTree::from_compact(&dt.to_compact()), Some(dt.clone()));
+
+    let path = env::temp_dir().join("hustle_book_test.csv");
+    let _ = fs::remove_file(&path);
+    let mut book = Book::load(&path).unwrap();
+    let data = data_key(DEFHDP, DEFLDP).unwrap();
+    let key = BookKey::new(bank_key(&gwb, &awb), data, 5, 6, false, &sd, "SALET.bbbbb.");
+    assert_eq!(key.gamestate, "salet.bbbbb");
+    book.insert(key.clone(), dt.clone());
+    book.save().unwrap();
+
+    let book = Book::load(&path).unwrap();
+    assert_eq!(book.len(), 1);
+    assert_eq!(book.get(&key), Some(&dt));
+    // other data or pruning can solve differently
+    assert_eq!(book.get(&BookKey { data: data ^ 1, ..key.clone() }), None);
+    assert_eq!(book.get(&BookKey { lbprune: true, ..key.clone() }), None);
+
+    // older formats are dropped, not misread
+    let old = "bank,wlen,turns,mode,ntops1,ntops2,ecut,exact,objective,gamestate,dtree";
+    fs::write(&path, format!("{}\n{:016x},5,6,E,2,200,15,false,tot,salet.bbbbb,{}\n",
+                             old, key.bank, dt.to_compact())).unwrap();
+    assert_eq!(Book::load(&path).unwrap().len(), 0);
+    fs::remove_file(&path).unwrap();
+  }
+}
@@ -1,6 +1,7 @@
 use std::collections::hash_map::DefaultHasher;
 use std::collections::VecDeque;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use super::state::State;
 use crate::ds::*;
@@ -29,6 +30,11 @@ impl Cache {
     Self { n, m, table }
   }
 
+  /// empty cache with the same dimensions
+  pub fn fresh(&self) -> Self {
+    Self::new(self.n, self.m)
+  }
+
   pub fn get_row(&mut self, state: &State) -> Option<&mut VecDeque<Entry>> {
     let mut h = DefaultHasher::new();
     state.hash(&mut h);
@@ -62,9 +68,14 @@ impl Cache {
 }
 
 impl Entry {
-  // check if equal
+  // check if equal (the guess fingerprint only rules out most sets quickly)
   pub fn check(&self, state: &State) -> bool {
-    self.state.n == state.n && self.state.aws == state.aws
+    Arc::ptr_eq(&self.state.fbt, &state.fbt)
+      && self.state.n == state.n
+      && self.state.hard == state.hard
+      && self.state.gkey == state.gkey
+      && self.state.aws == state.aws
+      && (Arc::ptr_eq(&self.state.gws, &state.gws) || self.state.gws == state.gws)
   }
 }
 
@@ -106,4 +117,37 @@ mod test {
     println!("row size: {}", cache.table.get(0).unwrap().len());
     assert!(cache.read(&state).is_none());
   }
+
+  #[test]
+  fn hard_gws() {
+    let mut sd = SData::new2(2, 200);
+    let state = State::random(20);
+    let dt = state.solve(&sd, u32::MAX).unwrap();
+
+    let mut cache = Cache::new(1, 5);
+    cache.add(state.clone(), dt.clone());
+
+    // same answers but filtered guesses shouldn't hit
+    let mut state2 = state.clone();
+    state2.hard = true;
+    assert!(cache.read(&state2).is_none());
+    let gw = state.answers()[0];
+    let fb = Feedback::from_str("bbbbb").unwrap();
+    let state3 = state2.clone().fb_follow(gw, fb);
+    let mut state4 = state3.clone();
+    state4.aws = state.aws.clone();
+    state4.n = state.n;
+    assert!(cache.read(&state4).is_none());
+
+    // nor should other guesses with a colliding fingerprint
+    let mut state5 = state4.clone();
+    state5.hard = false;
+    state5.gkey = state.gkey;
+    assert_ne!(state5.gws, state.gws);
+    assert!(cache.read(&state5).is_none());
+
+    // hard states are cached by their guesses
+    cache.add(state3.clone(), dt.clone());
+    assert_eq!(cache.read(&state3).unwrap().clone(), dt);
+  }
 }
@@ -0,0 +1,162 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+
+use crate::ds::*;
+
+/// feedback ids of guesses against answers, shared by every state of a solve
+pub struct FbTable {
+  pub gws: Vec<Word>,
+  pub aws: Vec<Word>,
+  pub wlen: u8,
+  gidxs: HashMap<Word, u32>,
+  aidxs: HashMap<Word, u32>,
+  // answer index of each guess, if it is one
+  g2a: Vec<Option<u32>>,
+  // feedback ids by guess, each row computed when it would have paid off
+  rows: Vec<OnceLock<Box<[u32]>>>,
+  // feedbacks computed directly by guess, until its row is filled
+  spent: Vec<AtomicUsize>,
+}
+
+impl FbTable {
+  pub fn new(gws: Vec<Word>, aws: Vec<Word>) -> Self {
+    let wlen = gws.first().or(aws.first()).map_or(NLETS as u8, |w| w.wlen);
+    let gidxs = gws.iter().enumerate().map(|(i, w)| (*w, i as u32)).collect();
+    let aidxs: HashMap<Word, u32> = aws.iter().enumerate().map(|(i, w)| (*w, i as u32)).collect();
+    let g2a = gws.iter().map(|w| aidxs.get(w).cloned()).collect();
+    let rows = (0..gws.len()).map(|_| OnceLock::new()).collect();
+    let spent = (0..gws.len()).map(|_| AtomicUsize::new(0)).collect();
+    Self { gws, aws, wlen, gidxs, aidxs, g2a, rows, spent }
+  }
+
+  pub fn from_banks(gwb: &WBank, awb: &WBank) -> Self {
+    Self::new(gwb.data.clone(), awb.data.clone())
+  }
+
+  /// compute every row now (in parallel) instead of when first needed
+  pub fn fill(&self) {
+    (0..self.gws.len()).into_par_iter().for_each(|gi| {
+      self.row(gi as u32);
+    });
+  }
+
+  fn make_row(&self, gw: &Word) -> Box<[u32]> {
+    self.aws.iter().map(|aw| fb_id(*gw, *aw)).collect()
+  }
+
+  /// feedback ids of a guess by index against every answer
+  pub fn row(&self, gi: u32) -> &[u32] {
+    self.rows[gi as usize].get_or_init(|| self.make_row(&self.gws[gi as usize]))
+  }
+
+  /// feedback ids of any word against every answer
+  pub fn word_row(&self, gw: &Word) -> Cow<'_, [u32]> {
+    match self.gindex(gw) {
+      Some(gi) => Cow::Borrowed(self.row(gi)),
+      None => Cow::Owned(self.make_row(gw).into_vec()),
+    }
+  }
+
+  /// feedback ids of any word against some answers, computing them directly
+  /// until that has cost as much as filling the guess's row (at most twice
+  /// the work of never filling, and no more once filled)
+  pub fn ids<I>(&self, gw: &Word, ais: I) -> Vec<u32>
+  where I: IntoIterator<Item = u32>, I::IntoIter: ExactSizeIterator, {
+    let ais = ais.into_iter();
+    let n = ais.len();
+    let row = self.gindex(gw).and_then(|gi| {
+      let gi = gi as usize;
+      if let Some(row) = self.rows[gi].get() {
+        return Some(row);
+      }
+      let spent = self.spent[gi].fetch_add(n, Ordering::Relaxed);
+      if spent + n < self.aws.len() {
+        return None;
+      }
+      Some(self.rows[gi].get_or_init(|| self.make_row(gw)))
+    });
+    match row {
+      Some(row) => ais.map(|ai| row[ai as usize]).collect(),
+      None => ais.map(|ai| fb_id(*gw, self.aws[ai as usize])).collect(),
+    }
+  }
+
+  pub fn get(&self, gi: u32, ai: u32) -> u32 {
+    self.row(gi)[ai as usize]
+  }
+
+  pub fn gindex(&self, gw: &Word) -> Option<u32> {
+    self.gidxs.get(gw).cloned()
+  }
+
+  pub fn aindex(&self, aw: &Word) -> Option<u32> {
+    self.aidxs.get(aw).cloned()
+  }
+
+  /// answer index of a guess by index
+  pub fn ganswer(&self, gi: u32) -> Option<u32> {
+    self.g2a[gi as usize]
+  }
+
+  /// indices of answers (which must be in the table)
+  pub fn aindices(&self, aws: &[Word]) -> Vec<u32> {
+    aws.iter()
+      .map(|aw| self.aindex(aw).expect("answer not in table!"))
+      .collect()
+  }
+
+  /// number of possible feedbacks
+  pub fn nfbs(&self) -> usize {
+    3usize.pow(self.wlen as u32)
+  }
+
+  /// id of all greens
+  pub fn correct_id(&self) -> u32 {
+    self.nfbs() as u32 - 1
+  }
+}
+
+impl PartialEq for FbTable {
+  fn eq(&self, other: &Self) -> bool {
+    self.gws == other.gws && self.aws == other.aws
+  }
+}
+
+impl Eq for FbTable {}
+
+impl fmt::Debug for FbTable {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "FbTable({}x{}, wlen={})", self.gws.len(), self.aws.len(), self.wlen)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn table_ids() {
+    let (gwb, awb) = WBank::from2(DEFWBP, 5).unwrap();
+    let fbt = FbTable::from_banks(&gwb, &awb);
+    let mut rng = rand::thread_rng();
+    for gw in gwb.pick(&mut rng, 20) {
+      let gi = fbt.gindex(&gw).unwrap();
+      for (ai, aw) in awb.data.iter().enumerate() {
+        let id = fbt.get(gi, ai as u32);
+        assert_eq!(id, fb_id(gw, *aw));
+        assert_eq!(Feedback::from_id(id, 5), Feedback::from(gw, *aw).unwrap());
+      }
+    }
+
+    // words outside the table still get feedback
+    let gw = Word::from_str("zzzzz").unwrap();
+    assert!(fbt.gindex(&gw).is_none());
+    assert_eq!(fbt.word_row(&gw)[0], fb_id(gw, awb.data[0]));
+    assert_eq!(fbt.ids(&gw, [1, 0]), vec![fb_id(gw, awb.data[1]), fb_id(gw, awb.data[0])]);
+  }
+}
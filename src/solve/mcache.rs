@@ -0,0 +1,146 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::iter::zip;
+use std::sync::Arc;
+
+use super::fbtable::FbTable;
+use super::multistate::{MState, MTree};
+use super::state::gws_key;
+use crate::ds::*;
+
+/// multi-board cache of each position's best guess, shared by transpositions
+#[derive(Debug, PartialEq, Clone)]
+pub struct MCache {
+  n: usize, // number of sets
+  m: usize, // max set length
+  table: Vec<VecDeque<MEntry>>,
+}
+
+/// canonical position: unfinished boards' answers (and guess keys) in sorted order
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct MKey {
+  boards: Vec<(Vec<u32>, u64)>,
+  // fingerprint of the guesses allowed now, which under HardRule::First
+  // depend on the boards' order
+  gkey: u64,
+  turns: u32,
+  hard: bool,
+  hrule: HardRule,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MEntry {
+  fbt: Arc<FbTable>,
+  key: MKey,
+  eval: f64,
+  word: Word,
+}
+
+impl MKey {
+  pub fn new(state: &MState) -> Self {
+    let mut boards: Vec<(Vec<u32>, u64)> = zip(&state.awss, &state.gkeys)
+      .zip(&state.finished)
+      .filter(|(_, fin)| !**fin)
+      .map(|((aws, gkey), _)| {
+        let mut aws = aws.clone();
+        aws.sort_unstable();
+        (aws, *gkey)
+      })
+      .collect();
+    boards.sort_unstable();
+    // every guess is allowed outside hard mode
+    let gkey = if state.hard { gws_key(&state.gws) } else { 0 };
+    Self { boards, gkey, turns: state.turns, hard: state.hard, hrule: state.hrule }
+  }
+}
+
+impl MCache {
+  pub fn new(n: usize, m: usize) -> Self {
+    let mut table = Vec::with_capacity(n);
+    for _i in 0..n {
+      table.push(VecDeque::new())
+    }
+    Self { n, m, table }
+  }
+
+  fn get_row(&mut self, key: &MKey) -> &mut VecDeque<MEntry> {
+    let mut h = DefaultHasher::new();
+    key.hash(&mut h);
+    let idx = h.finish() & (self.n as u64 - 1);
+    &mut self.table[idx as usize]
+  }
+
+  /// cached guess of a position (without its subtree)
+  pub fn read(&mut self, state: &MState) -> Option<MTree> {
+    let key = MKey::new(state);
+    let row = self.get_row(&key);
+    let i = row.iter().position(|ent| Arc::ptr_eq(&ent.fbt, &state.fbt) && ent.key == key)?;
+    // promote to front
+    let ent = row.remove(i).unwrap();
+    let mt = MTree::Node { eval: ent.eval, word: ent.word, fbmap: HashMap::new() };
+    row.push_front(ent);
+    Some(mt)
+  }
+
+  // assumes state not already in table
+  pub fn add(&mut self, state: &MState, mt: &MTree) {
+    let (eval, word) = match mt {
+      MTree::Leaf => return,
+      MTree::Node { eval, word, .. } => (*eval, *word),
+    };
+    let m = self.m;
+    let key = MKey::new(state);
+    let fbt = state.fbt.clone();
+    let row = self.get_row(&key);
+    row.push_front(MEntry { fbt, key, eval, word });
+    if row.len() > m {
+      row.pop_back();
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn transpositions() {
+    let (gwb, awb) = WBank::from2("/usr/share/hustle/bank1.csv", 5).unwrap();
+    let state = MState::new(gwb.data, vec![awb.data.clone(); 3], 5, 3, false);
+    let gw = Word::from_str("salet").unwrap();
+    let fb1 = Feedback::from_str("bgbgb").unwrap();
+    let fb2 = Feedback::from_str("byybb").unwrap();
+    let fbg = Feedback::from_str("ggggg").unwrap();
+    let state1 = state.fb_follow(gw, vec![fb1, fb2, fbg]);
+    let state2 = state.fb_follow(gw, vec![fbg, fb2, fb1]);
+    let state3 = state.fb_follow(gw, vec![fb1, fb1, fbg]);
+
+    // boards in another order or finished differently are the same position
+    assert_eq!(MKey::new(&state1), MKey::new(&state2));
+    assert_ne!(MKey::new(&state1), MKey::new(&state3));
+
+    let mut cache = MCache::new(1, 5);
+    let mt = MTree::Node { eval: 3.5, word: gw, fbmap: HashMap::new() };
+    assert!(cache.read(&state1).is_none());
+    cache.add(&state1, &mt);
+    assert_eq!(cache.read(&state2), Some(mt));
+    assert!(cache.read(&state3).is_none());
+
+    // states from another table shouldn't hit
+    let state4 = MState::new(state.fbt.gws.clone(), vec![awb.data; 3], 5, 3, false)
+      .fb_follow(gw, vec![fb1, fb2, fbg]);
+    assert!(cache.read(&state4).is_none());
+
+    // under HardRule::First, which board is first changes the guesses allowed
+    let mut hstate = MState::new(state.fbt.gws.clone(), vec![state.fbt.aws.clone(); 3], 5, 3, true);
+    let hstate1 = hstate.fb_follow(gw, vec![fb1, fb2, fbg]);
+    let hstate2 = hstate.fb_follow(gw, vec![fb2, fb1, fbg]);
+    assert_eq!(MKey::new(&hstate1), MKey::new(&hstate2));
+    hstate.hrule = HardRule::First;
+    let hstate1 = hstate.fb_follow(gw, vec![fb1, fb2, fbg]);
+    let hstate2 = hstate.fb_follow(gw, vec![fb2, fb1, fbg]);
+    assert_ne!(hstate1.gws, hstate2.gws);
+    assert_ne!(MKey::new(&hstate1), MKey::new(&hstate2));
+  }
+}
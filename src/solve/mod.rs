@@ -2,8 +2,21 @@ pub mod cache;
 pub use self::cache::Cache;
 pub mod state;
 pub use self::state::{fb_filter, SData, State};
+pub mod adversary;
+pub mod mcache;
+pub use self::mcache::MCache;
 pub mod multistate;
-pub use self::multistate::{MData, MState};
+pub use self::multistate::{MData, MState, MTree};
 pub mod adata;
 pub use self::adata::AData;
+pub mod fbtable;
+pub use self::fbtable::FbTable;
+pub mod wset;
+pub use self::wset::WSet;
 
+pub mod book;
+pub use self::book::{Book, BookKey};
+pub mod objective;
+pub use self::objective::Objective;
+pub mod stats;
+pub use self::stats::Stats;
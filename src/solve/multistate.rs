@@ -2,36 +2,86 @@ use std::iter::zip;
 use std::hash::{Hash, Hasher};
 use std::collections::{HashMap, HashSet};
 use std::cmp;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use rayon::prelude::*;
 
-use super::cache::Cache;
+use super::mcache::MCache;
 use super::adata::AData;
+use super::fbtable::FbTable;
+use super::state::{fb_filter, gws_key};
+use super::wset::WSet;
 use crate::ds::*;
 
-// TODO: also hash gws?
-// could also iteratively hash when forming the state
 type MFbMap<T> = HashMap<Vec<Feedback>, T>;
 
+/// partial multi-board decision tree, following only the sampled feedbacks
+#[derive(Debug, Clone, PartialEq)]
+pub enum MTree {
+  Leaf,
+  Node {
+    // expected number of guesses left
+    eval: f64,
+    // word
+    word: Word,
+    // children per sampled feedbacks (empty if found by an endgame shortcut or the cache)
+    fbmap: MFbMap<MTree>,
+  },
+}
+
+impl MTree {
+  pub fn follow(&self, fbs: &[Feedback]) -> Option<&MTree> {
+    match self {
+      MTree::Leaf => None,
+      MTree::Node { fbmap, .. } => fbmap.get(fbs),
+    }
+  }
+
+  /// expected number of guesses left
+  pub fn get_eval(&self) -> f64 {
+    match self {
+      MTree::Leaf => 0.,
+      MTree::Node { eval, .. } => *eval,
+    }
+  }
+
+  /// the guess to play, if unfinished
+  pub fn get_word(&self) -> Option<Word> {
+    match self {
+      MTree::Leaf => None,
+      MTree::Node { word, .. } => Some(*word),
+    }
+  }
+}
+
 /// solve data
 #[derive(Debug, Clone)]
 pub struct MData {
   /// analysis data
   pub adata: AData,
   /// cache
-  pub cache: Cache,
+  pub cache: MCache,
   /// number of top guesses to try
   pub nguesses: u32,
   /// number of top answers to try
   pub nanswers: u32,
   /// number of remaining words makes it "endgame"
   pub endgcutoff: u32,
+  /// seed for sampling answers
+  pub seed: u64,
+  /// enumerate every answer combination when there are at most this many
+  /// (always when there are at most nanswers)
+  pub exhaust: usize,
+  /// heuristic fraction of boards' extra guesses not shared with the neediest board's
+  pub unshared: f64,
+  /// heuristic penalty per guess the estimate goes over the turns left
+  pub risk: f64,
 }
 
 impl MData {
-  pub fn new(adata: AData, cache: Cache, nguesses: u32,
+  pub fn new(adata: AData, cache: MCache, nguesses: u32,
              nanswers: u32, endgcutoff: u32) -> Self {
     Self {
       adata,
@@ -39,64 +89,77 @@ impl MData {
       nguesses,
       nanswers,
       endgcutoff,
+      seed: 0,
+      exhaust: 0,
+      unshared: 0.5,
+      risk: 1.,
     }
   }
 
   pub fn new2(nguesses: u32, nanswers: u32) -> Self {
     let adata = AData::load(DEFHDP, DEFLDP).unwrap();
-    let cache = Cache::new(64, 8);
+    let cache = MCache::new(64, 8);
     Self::new(adata, cache, nguesses, nanswers, 15)
   }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct MState {
-  pub gws: Vec<Word>,
-  pub awss: Vec<Vec<Word>>,
+  /// feedback table shared with the parent state
+  pub fbt: Arc<FbTable>,
+  /// guesses in fbt allowed now, shared with the parent state unless filtered
+  pub gws: Arc<WSet>,
+  /// guesses in fbt consistent with each board's hints (only filtered in hard mode)
+  pub gwss: Vec<Arc<WSet>>,
+  // fingerprints of gwss, only recomputed when filtered
+  pub gkeys: Vec<u64>,
+  /// indices of each board's answers in fbt
+  pub awss: Vec<Vec<u32>>,
   pub wlen: u32,
   pub nwords: u32,
   pub turns: u32,
   pub finished: Vec<bool>,
   pub hard: bool,
-}
-
-pub fn fb_filter(gw: Word, fb: &Feedback, aws: &Vec<Word>) -> Vec<Word> {
-  aws.iter()
-    .cloned()
-    .filter(|aw| *fb == Feedback::from(gw, *aw).unwrap())
-    .collect()
-}
-
-pub fn fb_filter_all(gw: Word, fbs: &Vec<Feedback>, awss: &Vec<Vec<Word>>) -> Vec<Vec<Word>> {
-  zip(fbs, awss)
-    .map(|(fb, aws)| fb_filter(gw, fb, aws))
-    .collect()
+  /// which boards' hints guesses must reuse in hard mode
+  pub hrule: HardRule,
 }
 
 impl MState {
   pub fn new(gws: Vec<Word>, awss: Vec<Vec<Word>>,
              wlen: u32, nwords: u32, hard: bool) -> Self {
-    MState {
-      gws,
-      awss,
-      wlen,
-      nwords,
-      finished: vec![false; nwords as usize],
-      turns: nwords + NEXTRA as u32,
-      hard,
-    }
+    let finished = vec![false; nwords as usize];
+    MState::new2(gws, awss, wlen, nwords, finished, nwords + NEXTRA as u32, hard)
   }
 
   pub fn new2(gws: Vec<Word>, awss: Vec<Vec<Word>>, wlen: u32,
               nwords: u32, finished: Vec<bool>, turns: u32, hard: bool) -> Self {
+    // every board's answers in order of appearance
+    let mut seen = HashSet::new();
+    let aws = awss.iter()
+      .flatten()
+      .cloned()
+      .filter(|aw| seen.insert(*aw))
+      .collect();
+    let fbt = Arc::new(FbTable::new(gws, aws));
+    MState { wlen, nwords, finished, ..MState::from_table(fbt, &awss, turns, hard) }
+  }
+
+  /// state with boards of some of a table's answers, sharing the table
+  pub fn from_table(fbt: Arc<FbTable>, awss: &[Vec<Word>], turns: u32, hard: bool) -> Self {
+    let nwords = awss.len() as u32;
+    let gws = Arc::new(WSet::full(fbt.gws.len()));
     MState {
+      awss: awss.iter().map(|aws| fbt.aindices(aws)).collect(),
+      gwss: vec![gws.clone(); nwords as usize],
+      gkeys: vec![gws_key(&gws); nwords as usize],
+      wlen: fbt.wlen as u32,
+      fbt,
       gws,
-      awss,
-      wlen,
       nwords,
-      finished,
+      finished: vec![false; nwords as usize],
       turns,
       hard,
+      hrule: HardRule::Any,
     }
   }
 
@@ -104,39 +167,122 @@ impl MState {
     let (gwb, awb) = WBank::from2("/usr/share/hustle/bank1.csv", NLETS as u8).unwrap();
     let gws = gwb.data;
     let awss = vec![awb.data];
-    MState::new(gws, awss, 1, NLETS as u32, false)
+    MState::new(gws, awss, NLETS as u32, 1, false)
   }
 
+  /// number of answer combinations (saturating)
   pub fn size(&self) -> usize {
     self.awss.iter()
       .map(|aws| aws.len())
-      .fold(1, |a, b| a*b)
+      .fold(1, |a, b| a.saturating_mul(b))
   }
 
-//  pub fn fb_filter_guesses(&self, gw: &Word, fbs: Vec<Feedback>) -> Vec<Word> {
-//    if self.hard {
-//      fbs.iter()
-//        .map(|fb| HashSet::from_iter(fb_filter(*gw, *fb, &self.gws)))
-//        .fold(HashSet::<Word>::new(), |a, b| (a | b))
-//        .iter()
-//        .cloned()
-//        .collect()
-//    } else {
-//      self.gwss.clone()
-//    }
-//  }
+  /// if a word may be guessed now (any word outside hard mode)
+  pub fn allows(&self, gw: &Word) -> bool {
+    !self.hard || self.fbt.gindex(gw).is_some_and(|gi| self.gws.contains(gi))
+  }
+
+  // each board's consistent guesses after a guess, and the guesses the rule allows
+  fn fb_filter_guesses(&self, gw: &Word, ids: &[u32], finished: &[bool])
+                       -> (Arc<WSet>, Vec<Arc<WSet>>, Vec<u64>) {
+    let mut gwss = self.gwss.clone();
+    let mut gkeys = self.gkeys.clone();
+    for (i, id) in ids.iter().enumerate() {
+      if !finished[i] {
+        let fb = Feedback::from_id(*id, self.wlen as u8);
+        gwss[i] = Arc::new(fb_filter(&self.fbt, *gw, fb, &gwss[i]));
+        gkeys[i] = gws_key(&gwss[i]);
+      }
+    }
+
+    // finished boards' hints don't matter
+    let mut open = zip(&gwss, finished)
+      .filter(|(_, fin)| !**fin)
+      .map(|(gws, _)| gws);
+    let gws = match self.hrule {
+      HardRule::Any => Arc::new(open.fold(WSet::new(self.fbt.gws.len()), |a, b| a.union(b))),
+      HardRule::First => open.next().unwrap_or(&self.gws).clone(),
+    };
+    (gws, gwss, gkeys)
+  }
+
+  // each board's answers giving the feedback ids
+  fn fb_filter_all(&self, gw: &Word, ids: &[u32]) -> Vec<Vec<u32>> {
+    zip(ids, &self.awss)
+      .map(|(id, aws)| {
+        zip(aws, self.fbt.ids(gw, aws.iter().cloned()))
+          .filter(|(_, id2)| id2 == id)
+          .map(|(ai, _)| *ai)
+          .collect()
+      })
+      .collect()
+  }
+
+  // make child state
+  fn child(&self, gw: &Word, ids: &[u32], awss: Vec<Vec<u32>>, finished: Vec<bool>) -> Self {
+    let (gws, gwss, gkeys) = if self.hard {
+      self.fb_filter_guesses(gw, ids, &finished)
+    } else {
+      (self.gws.clone(), self.gwss.clone(), self.gkeys.clone())
+    };
+    MState {
+      fbt: self.fbt.clone(),
+      gws,
+      gwss,
+      gkeys,
+      awss,
+      wlen: self.wlen,
+      nwords: self.nwords,
+      finished,
+      turns: self.turns - 1,
+      hard: self.hard,
+      hrule: self.hrule,
+    }
+  }
 
   // get child state from guess and feedbacks
   pub fn fb_follow(&self, gw: Word, fbs: Vec<Feedback>) -> Self {
-    let gws = self.gws.clone(); // for now
-    let awss = fb_filter_all(gw, &fbs, &self.awss);
-    let mut finished = zip(self.finished.clone(), fbs)
+    let ids: Vec<u32> = fbs.iter().map(|fb| fb.to_id()).collect();
+    let awss = self.fb_filter_all(&gw, &ids);
+    let finished = zip(self.finished.clone(), fbs)
       .map(|(fin, fb)| fin || fb.is_correct())
       .collect();
-    MState::new2(gws, awss, self.wlen, self.nwords, finished, self.turns - 1, self.hard)
+    self.child(&gw, &ids, awss, finished)
   }
 
-  pub fn sample_answers(&self, rng: &mut ThreadRng, md: &MData) -> Vec<Vec<Word>> {
+  // rng for a guess from this state, independent of search order (seeded with
+  // fnv-1a like the book keys, since DefaultHasher can change between releases)
+  fn rng(&self, gw: &Word, md: &MData) -> StdRng {
+    let mut h = 0xcbf29ce484222325u64;
+    let mut feed = |x: u8| {
+      h ^= x as u64;
+      h = h.wrapping_mul(0x100000001b3);
+    };
+    md.seed.to_le_bytes().iter().for_each(|x| feed(*x));
+    // u32::MAX after each board's answers, which isn't an index
+    for aws in &self.awss {
+      aws.iter().chain([&u32::MAX]).for_each(|ai| ai.to_le_bytes().iter().for_each(|x| feed(*x)));
+    }
+    self.finished.iter().for_each(|fin| feed(*fin as u8));
+    self.turns.to_le_bytes().iter().for_each(|x| feed(*x));
+    gw.data[0..gw.wlen as usize].iter().for_each(|x| feed(*x));
+    StdRng::seed_from_u64(h)
+  }
+
+  /// every answer combination
+  pub fn all_answers(&self) -> Vec<Vec<u32>> {
+    self.awss.iter().fold(vec![vec![]], |combs, aws| {
+      combs.iter()
+        .flat_map(|comb| aws.iter().map(move |ai| {
+          let mut comb2 = comb.clone();
+          comb2.push(*ai);
+          comb2
+        }))
+        .collect()
+    })
+  }
+
+  pub fn sample_answers<R: Rng>(&self, rng: &mut R, md: &MData) -> Vec<Vec<u32>> {
     (0..md.nanswers as usize)
       .map(|_| {
         self.awss.iter()
@@ -147,19 +293,19 @@ impl MState {
       .collect()
   }
 
-  pub fn fb_partition(&self, gw: &Word, awss: Vec<Vec<Word>>) -> MFbMap<MState> {
+  pub fn fb_partition(&self, gw: &Word, awss: Vec<Vec<u32>>) -> MFbMap<MState> {
     // for now just randomly access and make feedback as you go
     // TODO: use top-k NRA, LARA, etc?
     let mut fbp = Mutex::new(MFbMap::new());
 
     // iterate over sample answer lists
     awss.par_iter().for_each(|aws| {
-      let fbs: Vec<Feedback> = aws.iter().map(|aw| Feedback::from(*gw, *aw).unwrap()).collect();
+      let ids = self.fbt.ids(gw, aws.iter().cloned());
+      let fbs: Vec<Feedback> = ids.iter().map(|id| Feedback::from_id(*id, self.wlen as u8)).collect();
       if !fbp.lock().unwrap().contains_key(&fbs) {
-        let gws2 = self.gws.clone(); // for now
-        let awss2 = fb_filter_all(*gw, &fbs, &self.awss);
+        let awss2 = self.fb_filter_all(gw, &ids);
         let finished2 = zip(self.finished.clone(), fbs.clone()).map(|(fin, fb)| fin || fb.is_correct()).collect();
-        let state = MState::new2(gws2, awss2, self.wlen, self.nwords, finished2, self.turns - 1, self.hard);
+        let state = self.child(gw, &ids, awss2, finished2);
         
         let mut fbp = fbp.lock().unwrap();
         fbp.insert(fbs.clone(), state);
@@ -173,39 +319,58 @@ impl MState {
   pub fn fb_counts(&self, gw: &Word) -> Vec<HashMap<Feedback, u32>> {
     self.awss.iter().map(|aws| {
       let mut map = HashMap::new();
-      for aw in aws {
-        let fb = Feedback::from(*gw, *aw).unwrap();
+      for id in self.fbt.ids(gw, aws.iter().cloned()) {
+        let fb = Feedback::from_id(id, self.wlen as u8);
         *map.entry(fb).or_insert(0) += 1;
       }
       map
     }).collect()
   }
   
-  // approximately quantify how good each guess is
-  // TODO bad for low numbers, add bonus for potentially correct guess
+  // chance a guess solves a board, and the expected guesses the board needs after it alone
+  fn board_approx(&self, gw: &Word, aws: &[u32], md: &MData) -> (f64, f64) {
+    let mut ids = self.fbt.ids(gw, aws.iter().cloned());
+    ids.sort_unstable();
+    let correct = self.fbt.correct_id();
+    let n = ids.len() as f64;
+    let mut p = 0.;
+    let mut tot = 0.;
+    for class in ids.chunk_by(|id1, id2| id1 == id2) {
+      if class[0] == correct {
+        p = class.len() as f64 / n;
+      } else {
+        tot += md.adata.get_approx(class.len()).unwrap();
+      }
+    }
+    (p, tot / n)
+  }
+
+  /// approximate expected number of guesses left, starting with a guess
   pub fn heuristic(&self, gw: &Word, md: &MData) -> f64 {
-    (self.fb_counts(gw), &self.finished)
-      .into_par_iter()
-      .map(|(fbc, &fin)| {
-        if fin { return 0. }
-
-        let mut tot = 0f64;
-        let mut sz = 0;
-        for (fb, n) in fbc {
-          if !fb.is_correct() {
-            tot += md.adata.get_approx(n as usize).unwrap();
-          }
-          sz += n;
-        }
-        tot / sz as f64
-      })
-      .sum()
+    // every board left needs its own last guess,
+    // but guesses before then also help other boards
+    let mut left = 0.;
+    let mut extras = vec![];
+    for (aws, fin) in zip(&self.awss, &self.finished) {
+      if *fin {continue}
+      let (p, tot) = self.board_approx(gw, aws, md);
+      left += 1. - p;
+      extras.push((tot - (1. - p)).max(0.));
+    }
+    let most = extras.iter().cloned().fold(0., f64::max);
+    let rest: f64 = extras.iter().sum::<f64>() - most;
+    let est = left + most + md.unshared * rest;
+
+    // running out of turns is worse than taking longer
+    let turns_left = self.turns.saturating_sub(1) as f64;
+    1. + est + md.risk * (est - turns_left).max(0.)
   }
 
   pub fn top_words(&self, md: &MData) -> Vec<Word> {
-    let mut tups: Vec<(Word, f64)> = self
-      .gws.clone()
+    let gis: Vec<u32> = self.gws.iter().collect();
+    let mut tups: Vec<(Word, f64)> = gis
       .into_par_iter()
+      .map(|gi| self.fbt.gws[gi as usize])
       .map(|gw| (gw, self.heuristic(&gw, md)))
       .collect();
     tups.sort_by(|(_, f1), (_, f2)| f1.partial_cmp(f2).unwrap());
@@ -216,23 +381,34 @@ impl MState {
       .collect()
   }
 
-  pub fn solve_given(&self, gw: Word, md: &mut MData) -> Option<f64> {
-    let awss_sample = self.sample_answers(&mut rand::thread_rng(), md);
-    let fbps = self.fb_partition(&gw, awss_sample);
+  pub fn solve_given(&self, gw: Word, md: &mut MData) -> Option<MTree> {
+    let awss_sample = if self.size() <= md.exhaust.max(md.nanswers as usize) {
+      self.all_answers()
+    } else {
+      self.sample_answers(&mut self.rng(&gw, md), md)
+    };
+    // sum in a fixed order so evaluations are reproducible
+    let mut fbps: Vec<(Vec<Feedback>, MState)> = self.fb_partition(&gw, awss_sample)
+      .into_iter()
+      .collect();
+    fbps.sort_unstable_by(|(fbs1, _), (fbs2, _)| fbs1.cmp(fbs2));
 
     let mut tot = 0.;
     let mut sz = 0;
-    for (fb, state) in fbps.iter() {
+    let mut fbmap = MFbMap::new();
+    for (fbs, state) in fbps {
       let sz2 = state.size();
-      tot += sz2 as f64 * state.solve(md)?;
+      let mt = state.solve(md)?;
+      tot += sz2 as f64 * mt.get_eval();
       sz += sz2;
+      fbmap.insert(fbs, mt);
     }
 
-    Some(1. + tot / sz as f64)
+    Some(MTree::Node { eval: 1. + tot / sz as f64, word: gw, fbmap })
   }
 
-  pub fn solve(&self, md: &mut MData) -> Option<f64> {
-    if self.finished.iter().all(|&fin| fin) {return Some(0.)}
+  pub fn solve(&self, md: &mut MData) -> Option<MTree> {
+    if self.finished.iter().all(|&fin| fin) {return Some(MTree::Leaf)}
     if self.turns == 0 {return None}
 
     let n_finished: usize = self.finished.iter().map(|&fin| fin as usize).sum();
@@ -241,67 +417,71 @@ impl MState {
 
     // one answer -> guess it
     for (aws, fin) in zip(&self.awss, &self.finished) {
-      if aws.len() == 1 && !fin {
-        return self.solve_given(*aws.get(0).unwrap(), md);
+      let aw = aws.first().map(|ai| self.fbt.aws[*ai as usize]);
+      if aws.len() == 1 && !fin && self.allows(&aw.unwrap()) {
+        return self.solve_given(aw.unwrap(), md);
       }
     }
 
     // check if a potential answer fixes rest
     if self.awss.iter().all(|aws| aws.len() < 15) {
-      let mut smallest_fix = usize::MAX;
+      let mut smallest_fix = None;
       for (aws, fin) in zip(&self.awss, &self.finished) {
-        if aws.len() >= smallest_fix || *fin {continue}
-        for aw in aws {
-          if self.fb_counts(&aw).iter().all(|fbc| fbc.iter().all(|(fb, ct)| *ct == 1)) {
-            smallest_fix = aws.len();
+        if smallest_fix.is_some_and(|(len, _)| aws.len() >= len) || *fin {continue}
+        for ai in aws {
+          let aw = self.fbt.aws[*ai as usize];
+          if self.allows(&aw) && self.fb_counts(&aw).iter().all(|fbc| fbc.iter().all(|(fb, ct)| *ct == 1)) {
+            smallest_fix = Some((aws.len(), aw));
+            break;
           }
         }
       }
 
-      if smallest_fix != usize::MAX {
-        return Some(n_unfinished as f64 - 1f64 / smallest_fix as f64);
+      if let Some((len, aw)) = smallest_fix {
+        // this guess, then one more for each other board, and for this one
+        // unless it was the answer
+        let eval = n_unfinished as f64 + 1.0 - 1.0 / len as f64;
+        return Some(MTree::Node { eval, word: aw, fbmap: MFbMap::new() });
       }
     }
 
     // check cache
-//    if !self.hard {
-//      if let Some(dt) = md.cache.read(self) {
-//        return Some(dt.clone());
-//      }
-//    }
+    if let Some(mt) = md.cache.read(self) {
+      return Some(mt);
+    }
 
     // find best top word
-    let mut tot = f64::INFINITY;
+    let mut best: Option<MTree> = None;
     let tops = self.top_words(md);
     for w in tops {
-      let tot2 = self.solve_given(w, md);
-      if let Some(tot2) = self.solve_given(w, md) {
-        if tot2 < tot {tot = tot2}
+      if let Some(mt) = self.solve_given(w, md) {
+        let eval = mt.get_eval();
+        if best.as_ref().is_none_or(|mt2| eval < mt2.get_eval()) {
+          best = Some(mt);
+        }
         // return if best case
-        if tot2 == n_unfinished as f64 {return Some(tot2)}
+        if eval == n_unfinished as f64 {break}
       }
     }
 
     // add cache
-//    if !self.hard {
-//      if let Some(ref dt) = dt {
-//        md.cache.add(self.clone(), dt.clone());
-//      }
-//    }
-
-    if tot == f64::INFINITY { None } else { Some(tot) }
+    if let Some(ref mt) = best {
+      md.cache.add(self, mt);
+    }
+    best
   }
 }
 
 impl<'a> Hash for MState {
   fn hash<H: Hasher>(&self, h: &mut H) {
-    self.gws.hash(h);
+    self.gkeys.hash(h);
     self.awss.hash(h);
     self.finished.hash(h);
     self.wlen.hash(h);
     self.nwords.hash(h);
     self.turns.hash(h);
     self.hard.hash(h);
+    self.hrule.hash(h);
   }
 }
 
@@ -332,8 +512,15 @@ mod test {
 //      Feedback::from_str("bbbbb").unwrap(),
 //    ]);
 
-    let tot = state.solve(&mut md);
-    assert!(tot.is_some());
+    // the policy starts with a guess and follows the sampled feedbacks
+    let mt = state.solve(&mut md).unwrap();
+    assert!(mt.get_word().is_some());
+    if let MTree::Node { fbmap, .. } = &mt {
+      for (fbs, mt2) in fbmap {
+        assert_eq!(mt.follow(fbs), Some(mt2));
+        assert!(mt2.get_eval() >= 0.);
+      }
+    }
   }
 
   #[test]
@@ -344,7 +531,7 @@ mod test {
 
     // FLICK, ICILY
     // ENSUE, GUESS, GUISE, ISSUE
-    // guessing ENSUE/ISSUE gives 1.75
+    // guessing ENSUE/ISSUE gives 2.75
     // which should be found in endgame check
     state = state.fb_follow(Word::from_str("salet").unwrap(), vec![
       Feedback::from_str("bbybb").unwrap(),
@@ -354,7 +541,100 @@ mod test {
       Feedback::from_str("ybbbb").unwrap(),
       Feedback::from_str("bbybb").unwrap(),
     ]);
-    assert!(state.solve(&mut md) == Some(1.75));
+    let mt = state.solve(&mut md).unwrap();
+    assert_eq!(mt.get_eval(), 2.75);
+    let w = mt.get_word().unwrap().to_string();
+    assert!(w == "ENSUE" || w == "ISSUE");
+  }
+
+  #[test]
+  fn heuristic_answers() {
+    let (gwb, awb) = WBank::from2("/usr/share/hustle/bank1.csv", 5).unwrap();
+    let fbt = Arc::new(FbTable::from_banks(&gwb, &awb));
+    let aws: Vec<Word> = ["flick", "ensue", "guess"].iter().map(|s| Word::from_str(s).unwrap()).collect();
+    let state = MState::from_table(fbt, &[vec![aws[0]], vec![aws[1], aws[2]]], 4, false);
+    let md = MData::new2(1, 1);
+
+    // guessing an answer saves a guess (either way takes 2.5 on average)
+    assert_eq!(state.heuristic(&aws[0], &md), 2.5);
+    assert_eq!(state.heuristic(&aws[1], &md), 2.5);
+    assert!(state.heuristic(&Word::from_str("salet").unwrap(), &md) > 2.5);
+    assert!(aws.contains(&state.top_words(&md)[0]));
+
+    // and more so with no turns to spare
+    let mut state2 = state.clone();
+    state2.turns = 2;
+    let gw = Word::from_str("salet").unwrap();
+    assert!(state2.heuristic(&gw, &md) - state.heuristic(&gw, &md) >= md.risk);
+  }
+
+  #[test]
+  fn hard_guesses() {
+    let (gwb, awb) = WBank::from2("/usr/share/hustle/bank1.csv", 5).unwrap();
+    let state = MState::new(gwb.data, vec![awb.data; 3], 5, 3, true);
+    let gw = Word::from_str("salet").unwrap();
+    let fb1 = Feedback::from_str("bgbgb").unwrap();
+    let fb2 = Feedback::from_str("byybb").unwrap();
+    let fbg = Feedback::from_str("ggggg").unwrap();
+    let fits = |gi: u32, fb: Feedback| Feedback::from(gw, state.fbt.gws[gi as usize]) == Some(fb);
+
+    // guesses must be consistent with some unfinished board
+    let state1 = state.fb_follow(gw, vec![fb1, fbg, fb2]);
+    assert!(state1.gws.iter().all(|gi| fits(gi, fb1) || fits(gi, fb2)));
+    assert!(state1.gws.iter().any(|gi| fits(gi, fb2)));
+    assert!(state1.awss.iter().flatten().all(|ai| state1.allows(&state1.fbt.aws[*ai as usize])));
+    assert!(!state1.allows(&gw));
+
+    // or with the first unfinished board
+    let mut state2 = state.clone();
+    state2.hrule = HardRule::First;
+    let state2 = state2.fb_follow(gw, vec![fbg, fb2, fb1]);
+    assert!(state2.gws.iter().all(|gi| fits(gi, fb2)));
+    assert_eq!(state2.gws, state2.gwss[1]);
+
+    // easy mode never filters
+    let mut state3 = state.clone();
+    state3.hard = false;
+    assert_eq!(state3.fb_follow(gw, vec![fb1, fbg, fb2]).gws, state.gws);
+
+    let mut md = MData::new2(3, 3);
+    let mt = state1.solve(&mut md).unwrap();
+    assert!(state1.allows(&mt.get_word().unwrap()));
+  }
+
+  #[test]
+  fn solve_seeded() {
+    let (gwb, awb) = WBank::from2("/usr/share/hustle/bank1.csv", 5).unwrap();
+    let mut state = MState::new(gwb.data, vec![awb.data; 4], 5, 4, false);
+    state = state.fb_follow(Word::from_str("salet").unwrap(), vec![
+      Feedback::from_str("bgbgb").unwrap(),
+      Feedback::from_str("byybb").unwrap(),
+      Feedback::from_str("bbybb").unwrap(),
+      Feedback::from_str("byyyb").unwrap(),
+    ]);
+
+    // the same seed gives the same policy
+    let mut md1 = MData::new2(3, 3);
+    let mut md2 = MData::new2(3, 3);
+    md1.seed = 7;
+    md2.seed = 7;
+    assert_eq!(state.solve(&mut md1), state.solve(&mut md2));
+
+    // small positions are enumerated, so every seed agrees
+    state = state.fb_follow(Word::from_str("courd").unwrap(), vec![
+      Feedback::from_str("bbbbb").unwrap(),
+      Feedback::from_str("bbbyb").unwrap(),
+      Feedback::from_str("ybbbb").unwrap(),
+      Feedback::from_str("bbbbb").unwrap(),
+    ]);
+    assert!(state.size() <= 10000);
+    md1.exhaust = 10000;
+    md2.exhaust = 10000;
+    md2.seed = 8;
+    let gw = Word::from_str("brink").unwrap();
+    let ev1 = state.solve_given(gw, &mut md1).unwrap().get_eval();
+    let ev2 = state.solve_given(gw, &mut md2).unwrap().get_eval();
+    assert_eq!(ev1, ev2);
   }
 
 //  #[test]
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use crate::ds::*;
+
+/// something to minimize about the number of guesses each answer takes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Criterion {
+  /// total number of guesses ("tot")
+  Tot,
+  /// maximum number of guesses ("max")
+  Max,
+  /// number of answers not solved within k guesses ("wink")
+  Over(u32),
+  /// number of answers solved in exactly k guesses ("nk")
+  Exactly(u32),
+}
+
+/// lexicographic combination of criteria to minimize
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Objective {
+  pub crits: Vec<Criterion>,
+  /// the maximum number of turns, to make guess counts absolute
+  pub turns: u32,
+}
+
+impl Criterion {
+  fn parse(s: &str) -> Option<Self> {
+    if s == "tot" {
+      Some(Criterion::Tot)
+    } else if s == "max" {
+      Some(Criterion::Max)
+    } else if let Some(k) = s.strip_prefix("win") {
+      Some(Criterion::Over(k.parse().ok()?))
+    } else if let Some(k) = s.strip_prefix('n') {
+      Some(Criterion::Exactly(k.parse().ok()?))
+    } else {
+      None
+    }
+  }
+
+  // cost given hist of guesses, offset by guesses already made
+  fn cost(&self, hist: &[u32], offset: u32) -> u32 {
+    let guesses = (1..).map(|i| i + offset);
+    match self {
+      Criterion::Tot => hist.iter().zip(guesses).map(|(x, g)| x * g).sum(),
+      Criterion::Max => hist.iter().zip(guesses)
+        .filter(|(x, _)| **x > 0)
+        .map(|(_, g)| g)
+        .max()
+        .unwrap_or(0),
+      Criterion::Over(k) => hist.iter().zip(guesses)
+        .filter(|(_, g)| g > k)
+        .map(|(x, _)| x)
+        .sum(),
+      Criterion::Exactly(k) => hist.iter().zip(guesses)
+        .filter(|(_, g)| g == k)
+        .map(|(x, _)| x)
+        .sum(),
+    }
+  }
+}
+
+impl fmt::Display for Criterion {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Criterion::Tot => write!(f, "tot"),
+      Criterion::Max => write!(f, "max"),
+      Criterion::Over(k) => write!(f, "win{}", k),
+      Criterion::Exactly(k) => write!(f, "n{}", k),
+    }
+  }
+}
+
+impl Objective {
+  /// minimize the total number of guesses
+  pub fn total() -> Self {
+    Self {
+      crits: vec![Criterion::Tot],
+      turns: NGUESSES as u32,
+    }
+  }
+
+  /// parse comma separated criteria (e.g. "n6,tot")
+  pub fn parse(s: &str, turns: u32) -> Option<Self> {
+    let crits = s.split(',')
+      .map(|s| Criterion::parse(s.trim()))
+      .collect::<Option<Vec<Criterion>>>()?;
+    if crits.is_empty() {
+      return None;
+    }
+    Some(Self { crits, turns })
+  }
+
+  /// costs of a tree solving from a state with n turns left
+  pub fn cost(&self, dt: &DTree, n: u32) -> Vec<u32> {
+    let offset = self.turns.saturating_sub(n);
+    self.crits.iter()
+      .map(|crit| crit.cost(dt.get_hist(), offset))
+      .collect()
+  }
+
+  pub fn better(&self, dt1: &DTree, dt2: &DTree, n: u32) -> bool {
+    if self.prunes() {
+      dt1.get_tot() < dt2.get_tot()
+    } else {
+      self.cost(dt1, n) < self.cost(dt2, n)
+    }
+  }
+
+  /// if bounding by the total is sound
+  pub fn prunes(&self) -> bool {
+    self.crits == [Criterion::Tot]
+  }
+
+  /// if the best tree is made of each partition's best tree
+  pub fn decomposes(&self) -> bool {
+    self.crits.len() == 1 || !self.crits.contains(&Criterion::Max)
+  }
+}
+
+impl fmt::Display for Objective {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let s = self.crits.iter()
+      .map(|crit| crit.to_string())
+      .collect::<Vec<String>>()
+      .join(",");
+    write!(f, "{}", s)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::solve::{SData, State};
+
+  #[test]
+  fn objectives() {
+    let obj = Objective::parse("n6,win4,max,tot", 6).unwrap();
+    assert_eq!(obj.to_string(), "n6,win4,max,tot");
+    assert!(Objective::parse("blah", 6).is_none());
+
+    // max depth should never be worse than when minimizing the total
+    let sd1 = SData::new2(2, 200);
+    let mut sd2 = SData::new2(2, 200);
+    sd2.obj = Objective::parse("max,tot", 6).unwrap();
+    for _ in 0..5 {
+      let state = State::random(50);
+      let dt1 = state.solve(&sd1, u32::MAX).unwrap();
+      let dt2 = state.solve(&sd2, u32::MAX).unwrap();
+      let hist1 = dt1.get_hist();
+      let hist2 = dt2.get_hist();
+      assert_eq!(hist1.iter().sum::<u32>() as usize, state.aws.len());
+      assert!(hist2.len() <= hist1.len());
+      assert!(dt1.get_tot() <= dt2.get_tot());
+    }
+  }
+}
@@ -1,17 +1,27 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Mutex};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
 
+use lazy_static::lazy_static;
 use rand::Rng;
+use rand::seq::SliceRandom;
 use rayon::prelude::*;
 
 use super::cache::Cache;
 use super::adata::AData;
+use super::fbtable::FbTable;
+use super::objective::Objective;
+use super::wset::WSet;
+use super::stats::Stats;
 use crate::ds::*;
 
-// TODO: also hash gws?
-// could also iteratively hash when forming the state
-
-// maximum number of words solveable in two guesses
+// maximum number of words solveable in two guesses, found for the 5 letter banks
+// but used for every word length (so longer words may have two guess solutions
+// over this that only --exact finds)
 const MAX_TWOSOLVE: u32 = 20;
 
 /// solve data
@@ -27,6 +37,23 @@ pub struct SData {
   pub ntops2: u32,
   /// number of remaining words makes it "endgame"
   pub ecut: u32,
+  /// also prune with the generated lower bounds data, which is faster but
+  /// unproven, so better trees can be missed (otherwise only 2|A|-1)
+  pub lbprune: bool,
+  /// try every guess and only use proven bounds
+  pub exact: bool,
+  /// what to minimize
+  pub obj: Objective,
+  /// answer weights (otherwise all 1)
+  pub weights: Option<HashMap<Word, u32>>,
+  /// set to stop solving early
+  pub cancel: Arc<AtomicBool>,
+  /// time to cancel at
+  pub deadline: Option<Instant>,
+  /// search statistics
+  pub stats: Arc<Stats>,
+  /// report progress of the root, which has this many turns left
+  pub progress: Option<u32>,
 }
 
 impl SData {
@@ -39,6 +66,14 @@ impl SData {
       ntops1,
       ntops2,
       ecut,
+      lbprune: false,
+      exact: false,
+      obj: Objective::total(),
+      weights: None,
+      cancel: Arc::new(AtomicBool::new(false)),
+      deadline: None,
+      stats: Arc::new(Stats::new()),
+      progress: None,
     }
   }
 
@@ -47,6 +82,55 @@ impl SData {
     let cache = Cache::new(64, 8);
     Self::new(adata, cache, ntops1, ntops2, 15)
   }
+
+  /// if solving should stop, only returning complete trees found so far
+  pub fn cancelled(&self) -> bool {
+    if self.cancel.load(Ordering::Relaxed) {
+      return true;
+    }
+    if self.deadline.is_some_and(|dl| Instant::now() >= dl) {
+      self.cancel.store(true, Ordering::Relaxed);
+      return true;
+    }
+    false
+  }
+
+  pub fn weight(&self, w: &Word) -> u32 {
+    self.weights.as_ref().map_or(1, |ws| *ws.get(w).unwrap_or(&1))
+  }
+
+  /// total weight of answers
+  pub fn wsum(&self, state: &State) -> u32 {
+    match self.weights {
+      None => state.aws.len() as u32,
+      Some(_) => state.aws.iter().map(|ai| self.weight(&state.fbt.aws[ai as usize])).sum(),
+    }
+  }
+
+  /// all but one answer take at least two guesses
+  pub fn alpha(&self, state: &State) -> u32 {
+    match self.weights {
+      None => (2 * state.aws.len() as u32).saturating_sub(1),
+      Some(_) => {
+        let wmax = state.aws.iter()
+          .map(|ai| self.weight(&state.fbt.aws[ai as usize]))
+          .max()
+          .unwrap_or(0);
+        2 * self.wsum(state) - wmax
+      }
+    }
+  }
+
+  /// lower bound on the total of solving answers
+  pub fn lbound(&self, state: &State) -> u32 {
+    let alpha = self.alpha(state);
+    // generated lower bounds aren't proven or weighted
+    if self.lbprune && !self.exact && self.weights.is_none() {
+      self.adata.get_lbound(state.aws.len()).map_or(alpha, |lb| lb.max(alpha))
+    } else {
+      alpha
+    }
+  }
 }
 
 #[derive(Clone)]
@@ -57,37 +141,81 @@ struct GivenData {
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct State {
-  pub gws: Vec<Word>,
-  pub aws: Vec<Word>,
+  /// feedback table shared with the parent state
+  pub fbt: Arc<FbTable>,
+  /// guesses in fbt, shared with the parent state unless filtered
+  pub gws: Arc<WSet>,
+  /// answers in fbt
+  pub aws: WSet,
   pub wlen: u32,
   pub n: u32,
   pub hard: bool,
+  // fingerprint of gws, only recomputed when filtered
+  pub gkey: u64,
+}
+
+lazy_static! {
+  // table of the default bank for random states
+  static ref DEFFBT: Arc<FbTable> = {
+    let (gwb, awb) = WBank::from2(DEFWBP, NLETS as u8).unwrap();
+    Arc::new(FbTable::from_banks(&gwb, &awb))
+  };
+}
+
+/// guesses giving the same feedback as gw would
+pub fn fb_filter(fbt: &FbTable, gw: Word, fb: Feedback, gws: &WSet) -> WSet {
+  let ids: Vec<u32> = gws.iter()
+    .map(|gi| fb_id(gw, fbt.gws[gi as usize]))
+    .collect();
+  let id = fb.to_id();
+  gws.filter(&ids, |id2| id2 == id)
 }
 
-pub fn fb_filter(gw: Word, fb: Feedback, gws: &Vec<Word>) -> Vec<Word> {
-  gws
-    .iter()
-    .cloned()
-    .filter(|gw2| fb == Feedback::from(gw, *gw2).unwrap())
-    .collect()
+/// guesses giving a feedback the partial feedback matches
+pub fn pfb_filter(fbt: &FbTable, gw: Word, pfb: PFeedback, gws: &WSet) -> WSet {
+  if let Some(fb) = pfb.known() {
+    return fb_filter(fbt, gw, fb, gws);
+  }
+  let ids: Vec<u32> = gws.iter()
+    .map(|gi| fb_id(gw, fbt.gws[gi as usize]))
+    .collect();
+  gws.filter(&ids, |id| pfb.matches_id(id))
+}
+
+pub fn gws_key(gws: &WSet) -> u64 {
+  let mut h = DefaultHasher::new();
+  gws.hash(&mut h);
+  h.finish()
 }
 
 impl State {
   pub fn new(gws: Vec<Word>, aws: Vec<Word>, wlen: u32, hard: bool) -> Self {
+    State::new2(gws, aws, wlen, NGUESSES as u32, hard)
+  }
+
+  pub fn new2(gws: Vec<Word>, aws: Vec<Word>, wlen: u32, n: u32, hard: bool) -> Self {
+    let fbt = Arc::new(FbTable::new(gws, aws));
+    let gws = WSet::full(fbt.gws.len());
     State {
-      gws,
-      aws,
+      gkey: gws_key(&gws),
+      gws: Arc::new(gws),
+      aws: WSet::full(fbt.aws.len()),
+      fbt,
       wlen,
-      n: NGUESSES as u32,
+      n,
       hard,
     }
   }
 
-  pub fn new2(gws: Vec<Word>, aws: Vec<Word>, wlen: u32, n: u32, hard: bool) -> Self {
+  /// state with some of a table's answers, sharing the table
+  pub fn from_table(fbt: Arc<FbTable>, aws: &[Word], n: u32, hard: bool) -> Self {
+    let gws = WSet::full(fbt.gws.len());
     State {
-      gws,
-      aws,
-      wlen,
+      gkey: gws_key(&gws),
+      gws: Arc::new(gws),
+      aws: WSet::from_indices(fbt.aws.len(), fbt.aindices(aws)),
+      wlen: fbt.wlen as u32,
+      fbt,
       n,
       hard,
     }
@@ -95,97 +223,123 @@ impl State {
 
   pub fn new3() -> Self {
     let (gwb, awb) = WBank::from2("/usr/share/hustle/bank1.csv", NLETS as u8).unwrap();
-    State::new(gwb.data, awb.data, NLETS as u32, false)
+    State::new(gwb.data, awb.data, awb.wlen as u32, false)
   }
 
   pub fn random(maxlen: usize) -> Self {
-    let (gwb, awb) = WBank::from2("/usr/share/hustle/bank1.csv", 5).unwrap();
+    State::random_from(DEFFBT.clone(), maxlen)
+  }
+
+  /// state with up to maxlen random answers from a table
+  pub fn random_from(fbt: Arc<FbTable>, maxlen: usize) -> Self {
     let mut rng = rand::thread_rng();
-    let len = rng.gen_range(1..=maxlen);
-    State::new2(
-      gwb.data,
-      awb.pick(&mut rng, len),
-      NLETS as u32,
-      NGUESSES as u32,
-      false,
-    )
-  }
-
-  pub fn fb_follow(self, gw: Word, fb: Feedback) -> Self {
-    let gws = if self.hard {
-      fb_filter(gw, fb, &self.gws)
+    let len = rng.gen_range(1..=maxlen.min(fbt.aws.len()));
+    let aws: Vec<Word> = fbt.aws.choose_multiple(&mut rng, len).cloned().collect();
+    State::from_table(fbt, &aws, NGUESSES as u32, false)
+  }
+
+  /// the answer words
+  pub fn answers(&self) -> Vec<Word> {
+    self.aws.iter().map(|ai| self.fbt.aws[ai as usize]).collect()
+  }
+
+  /// the guess words
+  pub fn guesses(&self) -> Vec<Word> {
+    self.gws.iter().map(|gi| self.fbt.gws[gi as usize]).collect()
+  }
+
+  /// if a word may be guessed now (any word outside hard mode)
+  pub fn allows(&self, gw: &Word) -> bool {
+    !self.hard || self.fbt.gindex(gw).is_some_and(|gi| self.gws.contains(gi))
+  }
+
+  pub fn is_answer(&self, w: &Word) -> bool {
+    self.fbt.aindex(w).is_some_and(|ai| self.aws.contains(ai))
+  }
+
+  // is_answer for a guess by index, without hashing
+  fn is_answer_gi(&self, gi: u32) -> bool {
+    self.fbt.ganswer(gi).is_some_and(|ai| self.aws.contains(ai))
+  }
+
+  // make child state, only filtering gws in hard mode
+  fn child(&self, gw: Word, pfb: PFeedback, aws: WSet) -> Self {
+    let (gws, gkey) = if self.hard {
+      let gws = pfb_filter(&self.fbt, gw, pfb, &self.gws);
+      let gkey = gws_key(&gws);
+      (Arc::new(gws), gkey)
     } else {
-      self.gws
+      (self.gws.clone(), self.gkey)
     };
-    let aws = fb_filter(gw, fb, &self.aws);
-    State::new2(gws, aws, self.wlen, self.n - 1, self.hard)
-  }
-
-  pub fn fb_partition(&self, gw: &Word) -> FbMap<State> {
-    let mut map = FbMap::new();
-    for aw in &self.aws {
-      let fb = Feedback::from(*gw, *aw).unwrap();
-      let s2: &mut State = map.entry(fb).or_insert_with(|| {
-        let gws2 = if self.hard {
-          fb_filter(*gw, fb, &self.gws)
-        } else {
-          self.gws.clone()
-        };
-        State::new2(gws2, Vec::new(), self.wlen, self.n - 1, self.hard)
-      });
-      s2.aws.push(*aw);
+    State {
+      fbt: self.fbt.clone(),
+      gws,
+      aws,
+      wlen: self.wlen,
+      n: self.n - 1,
+      hard: self.hard,
+      gkey,
     }
-    map
   }
 
-  pub fn fb_partition_vec(&self, gw: &Word) -> Vec<(Feedback, State)> {
-    let mut awss = vec![Vec::new(); 3usize.pow(self.wlen)];
+  /// keep the answers giving any feedback a (partial) feedback matches
+  pub fn fb_follow<F: Into<PFeedback>>(self, gw: Word, fb: F) -> Self {
+    let pfb = fb.into();
+    let ids = self.fbt.ids(&gw, self.aws.iter());
+    let aws = match pfb.known() {
+      Some(fb) => {
+        let id = fb.to_id();
+        self.aws.filter(&ids, |id2| id2 == id)
+      }
+      None => self.aws.filter(&ids, |id| pfb.matches_id(id)),
+    };
+    self.child(gw, pfb, aws)
+  }
 
-    for aw in &self.aws {
-      awss[fb_id(*gw, *aw) as usize].push(*aw);
-    }
+  // feedback ids paired with answers, sorted by id
+  fn fb_sorted(&self, gw: &Word) -> Vec<(u32, u32)> {
+    let ids = self.fbt.ids(gw, self.aws.iter());
+    let mut pairs: Vec<(u32, u32)> = ids.into_iter().zip(self.aws.iter()).collect();
+    pairs.sort_unstable();
+    pairs
+  }
 
-    awss.iter()
-      .enumerate()
-      .filter(|(id, aws)| !aws.is_empty())
-      .map(|(id, aws)| {
-        let fb = Feedback::from_id(id as u32, self.wlen as u8);
-        let gws2 = if self.hard {
-          fb_filter(*gw, fb, &self.gws)
-        } else {
-          self.gws.clone()
-        };
-        let state = State::new2(gws2, aws.clone(), self.wlen, self.n-1, self.hard);
-        (fb, state)
+  pub fn fb_partition(&self, gw: &Word) -> Vec<(Feedback, State)> {
+    self.fb_sorted(gw)
+      .chunk_by(|(id1, _), (id2, _)| id1 == id2)
+      .map(|class| {
+        let fb = Feedback::from_id(class[0].0, self.wlen as u8);
+        let aws = WSet::from_indices(self.fbt.aws.len(), class.iter().map(|(_, ai)| *ai));
+        (fb, self.child(*gw, fb.into(), aws))
       })
       .collect()
   }
 
-  pub fn fb_counts(&self, gw: &Word) -> FbMap<u32> {
-    let mut map = FbMap::new();
-    for aw in &self.aws {
-      let fb = Feedback::from(*gw, *aw).unwrap();
-      *map.entry(fb).or_insert(0) += 1;
-    }
-    map
+  /// sizes of the answers' feedback classes
+  pub fn fb_counts(&self, gw: &Word) -> Vec<u32> {
+    let mut ids = self.fbt.ids(gw, self.aws.iter());
+    ids.sort_unstable();
+    ids.chunk_by(|id1, id2| id1 == id2)
+      .map(|class| class.len() as u32)
+      .collect()
   }
 
-  pub fn fb_counts_vec(&self, gw: &Word) -> Vec<u32> {
-    // initialize vec with zeros
-    let mut cts = vec![0; 3usize.pow(self.wlen)];
-
-    for aw in &self.aws {
-      cts[fb_id(*gw, *aw) as usize] += 1;
-    }
+  /// if a guess gives every answer a different feedback
+  pub fn splits(&self, gw: &Word) -> bool {
+    self.fb_counts(gw).iter().all(|ct| *ct == 1)
+  }
 
-    cts
+  // if a guess gives some answers different feedbacks
+  fn divides(&self, gw: &Word) -> bool {
+    self.fb_counts(gw).len() > 1
   }
 
   pub fn letter_evals(&self) -> (Vec<Vec<f64>>, Vec<f64>) {
     // get letter counts
     let mut gss = vec![vec![0usize; self.wlen as usize]; 26];
     let mut ys = vec![0usize; 26];
-    for aw in &self.aws {
+    for ai in self.aws.iter() {
+      let aw = &self.fbt.aws[ai as usize];
       for i in 0..(self.wlen as usize) {
         gss[aw.data[i] as usize][i] += 1;
         if !aw.data[0..i].contains(&aw.data[i]) {
@@ -207,7 +361,8 @@ impl State {
     (gss, ys)
   }
 
-  pub fn letter_heuristic(&self, gw: &Word, gss: &Vec<Vec<f64>>, ys: &Vec<f64>) -> f64 {
+  pub fn letter_heuristic(&self, gi: u32, gss: &[Vec<f64>], ys: &[f64]) -> f64 {
+    let gw = &self.fbt.gws[gi as usize];
     let mut h = 0f64;
     for i in 0..(self.wlen as usize) {
       h += gss[gw.data[i] as usize][i];
@@ -216,7 +371,7 @@ impl State {
       }
     }
 
-    if self.aws.contains(&gw) {
+    if self.is_answer_gi(gi) {
       h * 1.05
     } else {
       h
@@ -224,20 +379,12 @@ impl State {
   }
 
   pub fn heuristic(&self, gw: &Word, sd: &SData) -> f64 {
-    let h = if self.wlen <= 5 {
-      self.fb_counts_vec(gw)
-        .iter()
-        .filter(|x| **x > 0)
-        .map(|&x| sd.adata.get_approx(x as usize).unwrap())
-        .sum()
-    } else {
-      self.fb_counts(gw)
-        .iter()
-        .map(|(_, n)| sd.adata.get_approx(*n as usize).unwrap())
-        .sum()
-    };
+    let h: f64 = self.fb_counts(gw)
+      .iter()
+      .map(|&x| sd.adata.get_approx(x as usize).unwrap())
+      .sum();
 
-    if self.aws.contains(gw) {
+    if self.is_answer(gw) {
       h - 1.
     } else {
       h
@@ -245,17 +392,22 @@ impl State {
   }
 
   pub fn top_words(&self, sd: &SData) -> Vec<Word> {
+    if sd.exact {
+      return self.all_words(sd);
+    }
+
     // fast heuristic
     let (gss, ys) = self.letter_evals();
-    let mut tups: Vec<(Word, f64)> = self
-      .gws.clone()
+    let mut tups: Vec<(u32, f64)> = self.gws
+      .iter()
+      .collect::<Vec<u32>>()
       .into_par_iter()
-      .map(|gw| (gw, self.letter_heuristic(&gw, &gss, &ys)))
+      .map(|gi| (gi, self.letter_heuristic(gi, &gss, &ys)))
       .collect();
     tups.sort_by(|(_, f1), (_, f2)| f2.partial_cmp(f1).unwrap());
     let gws2 = tups.iter()
       .take(sd.ntops1 as usize)
-      .map(|(gw, _)| *gw)
+      .map(|(gi, _)| self.fbt.gws[*gi as usize])
       .collect::<Vec<Word>>();
     
     // slow heuristic
@@ -271,144 +423,215 @@ impl State {
       .collect()
   }
 
+  // every guess that splits the answers, best heuristic first
+  fn all_words(&self, sd: &SData) -> Vec<Word> {
+    let mut tups: Vec<(Word, f64)> = self
+      .guesses()
+      .into_par_iter()
+      .filter(|gw| self.is_answer(gw) || self.divides(gw))
+      .map(|gw| (gw, self.heuristic(&gw, sd)))
+      .collect();
+    tups.sort_by(|(_, f1), (_, f2)| f1.partial_cmp(f2).unwrap());
+    tups.iter().map(|(gw, _)| *gw).collect()
+  }
+
   pub fn solve_given(&self, gw: Word, sd: &SData, beta: u32) -> Option<DTree> {
     let alen = self.aws.len();
 
     // leaf if guessed
-    if alen == 1 && gw == *self.aws.get(0).unwrap() {
+    if alen == 1 && Some(gw) == self.aws.first().map(|ai| self.fbt.aws[ai as usize]) {
       return Some(DTree::Leaf);
     }
     // impossible guesses
     if self.n == 0
       || (self.n == 1 && alen > 1)
-      || (self.n == 2 && alen > MAX_TWOSOLVE as usize) {
+      || (self.n == 2 && alen > MAX_TWOSOLVE as usize && !sd.exact) {
       return None;
     }
     // check alpha = 2|A|-1
-    if beta <= 2 * (alen as u32) - 1 {
+    if beta <= sd.alpha(self) {
+      Stats::inc(&sd.stats.cutoffs);
       return None;
     }
 
-    let mut tot = alen as u32;
-    let mut fbm = FbMap::new();
+    let fbp = self.fb_partition(&gw);
 
-    if self.wlen <= 5 {
-      let fbpv = self.fb_partition_vec(&gw);
-      for (fb, s2) in fbpv {
-        if fb.is_correct() {
-          fbm.insert(fb, DTree::Leaf);
-        } else {
-          match s2.solve(sd, beta - tot) {
-            None => return None,
-            Some(dt) => {
-              tot += dt.get_tot();
-              fbm.insert(fb, dt);
-              if tot >= beta {
-                return None;
-              }
-            }
-          }
-        }
+    // lower bound of the unsolved partitions
+    let mut lb_rest: u32 = fbp.iter()
+      .filter(|(fb, _)| !fb.is_correct())
+      .map(|(_, s2)| sd.lbound(s2))
+      .sum();
+    let mut tot = sd.wsum(self);
+    if tot + lb_rest >= beta {
+      Stats::inc(&sd.stats.cutoffs);
+      return None;
+    }
+
+    let mut fbm = FbMap::new();
+    for (fb, s2) in fbp {
+      if sd.cancelled() {
+        return None;
       }
-    } else {
-      let fbp = self.fb_partition(&gw);
-      // let mut sfbp: Vec<(&Feedback, &State)> = fbp.iter().collect();
-      // sfbp.sort_by_key(|(fb, s)| s.aws.len());
-
-      for (fb, s2) in fbp {
-        if fb.is_correct() {
-          fbm.insert(fb, DTree::Leaf);
-        } else {
-          match s2.solve(sd, beta - tot) {
-            None => return None,
-            Some(dt) => {
-              tot += dt.get_tot();
-              fbm.insert(fb, dt);
-              if tot >= beta {
-                return None;
-              }
+      if fb.is_correct() {
+        fbm.insert(fb, DTree::Leaf);
+      } else {
+        lb_rest -= sd.lbound(&s2);
+        match s2.solve(sd, beta - tot - lb_rest) {
+          None => return None,
+          Some(dt) => {
+            tot += dt.get_tot();
+            fbm.insert(fb, dt);
+            if tot + lb_rest >= beta {
+              Stats::inc(&sd.stats.cutoffs);
+              return None;
             }
           }
         }
       }
     }
 
-    Some(DTree::Node {
-      tot,
-      word: gw,
-      fbmap: fbm,
-    })
+    Some(DTree::node(gw, fbm, sd.weight(&gw)))
   }
 
   pub fn solve(&self, sd: &SData, beta: u32) -> Option<DTree> {
     let alen = self.aws.len();
 
     // no more turns
-    if self.n == 0 {
+    if self.n == 0 || sd.cancelled() {
       return None;
     }
     // one answer -> guess it
     if alen == 1 {
-      return Some(DTree::Node {
-        tot: 1,
-        word: *self.aws.get(0).unwrap(),
-        fbmap: [(Feedback::from_str("GGGGG").unwrap(), DTree::Leaf)].into(),
-      });
+      let aw = self.fbt.aws[self.aws.first().unwrap() as usize];
+      return Some(DTree::node(
+        aw,
+        [(Feedback::correct(self.wlen as u8), DTree::Leaf)].into(),
+        sd.weight(&aw),
+      ));
     }
-    // check alpha = 2|A|-1
-    if beta <= 2 * (alen as u32) - 1 {
+    // check lower bound
+    if beta <= sd.lbound(self) {
+      Stats::inc(&sd.stats.cutoffs);
       return None;
     }
-    // check endgame if viable
-    if alen <= sd.ecut as usize {
-      for aw in self.aws.iter() {
-        if self.fb_counts(aw).values().all(|c| *c == 1) {
-          return self.solve_given(*aw, sd, beta);
-        }
+    // check endgame if viable (one of the heaviest answers splitting the rest,
+    // which meets alpha so is optimal for the total, but is only a guess with
+    // other objectives, so exact searches those)
+    if alen <= sd.ecut as usize && (sd.obj.prunes() || !sd.exact) {
+      let aws = self.answers();
+      let wmax = aws.iter().map(|aw| sd.weight(aw)).max().unwrap_or(0);
+      let aw = aws.into_iter().find(|aw| sd.weight(aw) == wmax && self.splits(aw));
+      if let Some(aw) = aw {
+        Stats::inc(&sd.stats.endgames);
+        return self.solve_given(aw, sd, beta);
       }
     }
     // check cache
-    if !self.hard {
-      let mut cache = sd.cache.lock().unwrap();
-      if let Some(dt) = cache.read(self) {
-        return Some(dt.clone());
-      }
+    if let Some(dt) = sd.cache.lock().unwrap().read(self) {
+      Stats::inc(&sd.stats.hits);
+      return Some(dt.clone());
     }
+    Stats::inc(&sd.stats.misses);
 
     // finally, check top words
+    Stats::inc(&sd.stats.nodes);
     let tws = self.top_words(&sd);
+    let ntws = tws.len();
+    let root = sd.progress == Some(self.n);
+    let ntried = AtomicU64::new(0);
     let gd = Mutex::new(GivenData{dt: None, beta});
+    // guessing an answer that splits the rest is the only way under 2|A|,
+    // which top words rarely find once beta is there (exact keeps trying, and
+    // so do weights, since a heavy answer can get far under)
+    let floor = if sd.exact || sd.weights.is_some() { sd.alpha(self) } else { 2 * sd.wsum(self) };
     tws.into_par_iter().for_each(|w| {
       let gd2 = gd.lock().unwrap().clone();
-      if gd2.beta <= 2 * alen as u32 {return}
-      let dt2 = self.solve_given(w, sd, gd2.beta);
+      let dt2 = if gd2.beta <= floor {
+        None
+      } else {
+        self.solve_given(w, sd, gd2.beta)
+      };
+      let mut gd = gd.lock().unwrap();
       if let Some(dt2) = dt2 {
-        let mut gd = gd.lock().unwrap();
-        if dt2.get_tot() < gd.beta {
-          gd.beta = dt2.get_tot();
+        let better = match gd.dt {
+          Some(ref dt) => sd.obj.better(&dt2, dt, self.n),
+          None => dt2.get_tot() < gd.beta,
+        };
+        if better {
+          // only bound other guesses by the total if minimizing it
+          if sd.obj.prunes() {
+            gd.beta = dt2.get_tot();
+          }
           gd.dt = Some(dt2);
         }
       }
+      if root {
+        let i = ntried.fetch_add(1, Ordering::Relaxed) + 1;
+        let best = gd.dt.as_ref()
+          .map_or("-".to_owned(), |dt| dt.get_tot().to_string());
+        eprint!("\r{}/{} candidates, best: {}, {:.1}s ",
+                i, ntws, best, sd.stats.elapsed());
+        io::stderr().flush().unwrap();
+      }
     });
 
     let gd = gd.into_inner().unwrap();
     let dt = gd.dt;
+    if root {
+      eprintln!();
+    }
 
-    // add cache
-    if !self.hard {
-      if let Some(ref dt) = dt {
-        let mut cache = sd.cache.lock().unwrap();
-        cache.add(self.clone(), dt.clone());
+    // add cache if all top words were tried
+    if let Some(ref dt) = dt {
+      if !sd.cancelled() {
+        sd.cache.lock().unwrap().add(self.clone(), dt.clone());
       }
     }
 
     dt
   }
+
+  /// solve with widening ntops2 until it reaches sd's or sd is cancelled,
+  /// returning the best complete tree found (the first, greedy pass always
+  /// finishes, so there's one unless it can't solve in time)
+  pub fn solve_anytime(&self, gw: Option<Word>, sd: &SData) -> Option<DTree> {
+    let mut best: Option<DTree> = None;
+    let mut ntops2 = 1;
+    loop {
+      // fresh cache, otherwise narrower subtrees are reused
+      let mut sd2 = sd.clone();
+      sd2.ntops2 = ntops2.min(sd.ntops2);
+      sd2.cache = Arc::new(Mutex::new(sd.cache.lock().unwrap().fresh()));
+      if ntops2 == 1 {
+        sd2.cancel = Arc::new(AtomicBool::new(false));
+        sd2.deadline = None;
+      }
+      let dt = match gw {
+        Some(gw) => self.solve_given(gw, &sd2, u32::MAX),
+        None => self.solve(&sd2, u32::MAX),
+      };
+      if let Some(dt) = dt {
+        let better = match best {
+          Some(ref best) => sd.obj.better(&dt, best, self.n),
+          None => true,
+        };
+        if better {
+          best = Some(dt);
+        }
+      }
+      if sd.cancelled() || ntops2 >= sd.ntops2 {
+        return best;
+      }
+      ntops2 *= 2;
+    }
+  }
 }
 
 impl<'a> Hash for State {
   fn hash<H: Hasher>(&self, h: &mut H) {
     self.n.hash(h);
+    self.hard.hash(h);
+    self.gkey.hash(h);
     self.aws.hash(h);
   }
 }
@@ -428,6 +651,28 @@ mod test {
     assert_eq!(state2, state3);
   }
 
+  #[test]
+  fn partial_follow() {
+    let mut state = State::new3();
+    state.hard = true;
+    let gw = Word::from_str("salet").unwrap();
+    let pfb = PFeedback::from_str("?bbbb").unwrap();
+    let fbs = ["bbbbb", "ybbbb", "gbbbb"].map(|s| Feedback::from_str(s).unwrap());
+
+    // same as following each matching feedback
+    let state1 = state.clone().fb_follow(gw, pfb);
+    let states: Vec<State> = fbs.iter().map(|fb| state.clone().fb_follow(gw, *fb)).collect();
+    let aws = states.iter().fold(WSet::new(state.fbt.aws.len()), |a, s| a.union(&s.aws));
+    let gws = states.iter().fold(WSet::new(state.fbt.gws.len()), |a, s| a.union(&s.gws));
+    assert_eq!(state1.aws, aws);
+    assert_eq!(*state1.gws, gws);
+    assert_eq!(state1.n, state.n - 1);
+
+    // known feedback is the same as a feedback
+    let fb = PFeedback::from_str("bbbbb").unwrap();
+    assert_eq!(state.clone().fb_follow(gw, fb), states[0]);
+  }
+
   // takes a while
   // #[test]
   fn simple_solve() {
@@ -449,4 +694,323 @@ mod test {
     // cannot solve in 2 guesses
     assert!(state.solve(&sd, u32::MAX).is_none());
   }
+
+  #[test]
+  fn exact_solve() {
+    let mut sd1 = SData::new2(2, 200);
+    let mut sd2 = SData::new2(2, 200);
+    sd2.exact = true;
+
+    // exact should never be worse than the heuristic solve
+    for _ in 0..5 {
+      let state = State::random(30);
+      let tot1 = state.solve(&sd1, u32::MAX).map_or(u32::MAX, |dt| dt.get_tot());
+      let tot2 = state.solve(&sd2, u32::MAX).map_or(u32::MAX, |dt| dt.get_tot());
+      assert!(tot2 <= tot1);
+    }
+
+    // bounded by the best total, exact still finds it
+    let state = split_state();
+    assert_eq!(state.solve(&sd2, 32).map(|dt| dt.get_tot()), Some(31));
+  }
+
+  // shank is the heaviest answer but doesn't split the rest, so guessing anode
+  // (which does) is far from the best
+  fn weighted_state() -> (State, HashMap<Word, u32>) {
+    let ws: Vec<Word> = ["anode", "shank", "wordy", "climb"].iter()
+      .map(|s| Word::from_str(s).unwrap())
+      .collect();
+    let weights = ws.iter().cloned().zip([1, 10, 1, 1]).collect();
+    (State::new(ws.clone(), ws, 5, false), weights)
+  }
+
+  #[test]
+  fn weighted_endgame() {
+    let (state, weights) = weighted_state();
+    let mut sd = SData::new2(1000, 10);
+    sd.weights = Some(weights);
+    let dt = state.solve(&sd, u32::MAX).unwrap();
+    assert_eq!(dt.get_tot(), 17);
+    assert!(matches!(dt, DTree::Node { word, .. } if word == Word::from_str("shank").unwrap()));
+  }
+
+  #[test]
+  fn exact_endgame() {
+    // exact should find the same costs with or without endgame shortcuts
+    let (state, weights) = weighted_state();
+    let mut states = vec![(state, Some(weights))];
+    for _ in 0..2 {
+      let state = State::random(4);
+      let weights = state.answers().iter().enumerate().map(|(i, aw)| (*aw, i as u32 % 3 + 1)).collect();
+      states.push((state, Some(weights)));
+    }
+    for (state, weights) in states {
+      for obj in ["tot", "n2,tot", "max,tot"] {
+        let mut sd1 = SData::new2(1000, 10);
+        let mut sd2 = SData::new2(1000, 10);
+        for sd in [&mut sd1, &mut sd2] {
+          sd.exact = true;
+          sd.weights = weights.clone();
+          sd.obj = Objective::parse(obj, NGUESSES as u32).unwrap();
+        }
+        sd2.ecut = 0;
+        let cost1 = state.solve(&sd1, u32::MAX).map(|dt| sd1.obj.cost(&dt, state.n));
+        let cost2 = state.solve(&sd2, u32::MAX).map(|dt| sd2.obj.cost(&dt, state.n));
+        assert_eq!(cost1, cost2, "{} {:?}", obj, state.answers());
+      }
+    }
+  }
+
+  // takes minutes: cargo test --release -- --ignored
+  #[test]
+  #[ignore]
+  fn exact_salet() {
+    // Selby's proven optimum for salet, over the original 2315 answers
+    let (gwb, mut awb) = WBank::from2("/usr/share/hustle/bank1.csv", 5).unwrap();
+    for s in ["agora", "pupal", "lynch", "fibre", "slave", "wench"] {
+      awb.data.push(Word::from_str(s).unwrap());
+    }
+    let state = State::new(gwb.data, awb.data, 5, false);
+    let mut sd = SData::new2(2, 200);
+    sd.exact = true;
+    let dt = state.solve_given(Word::from_str("salet").unwrap(), &sd, u32::MAX).unwrap();
+    assert_eq!((dt.get_tot(), state.aws.len()), (7920, 2315));
+  }
+
+  #[test]
+  fn weighted_solve() {
+    let sd1 = SData::new2(2, 200);
+    let mut sd2 = SData::new2(2, 200);
+    let state = State::random(50);
+
+    // unit weights shouldn't change anything
+    sd2.weights = Some(state.answers().iter().map(|aw| (*aw, 1)).collect());
+    let dt1 = state.solve(&sd1, u32::MAX).unwrap();
+    let dt2 = state.solve(&sd2, u32::MAX).unwrap();
+    assert_eq!(dt1.get_tot(), dt2.get_tot());
+
+    // tot is the weighted sum of depths
+    let mut sd3 = SData::new2(2, 200);
+    sd3.weights = Some(state.answers().iter().enumerate().map(|(i, aw)| (*aw, i as u32 % 3 + 1)).collect());
+    let dt3 = state.solve(&sd3, u32::MAX).unwrap();
+    assert_eq!(dt3.get_hist().iter().sum::<u32>(), sd3.wsum(&state));
+    assert_eq!(DTree::from_compact(&dt3.to_compact()), Some(dt3));
+  }
+
+  #[test]
+  fn tree_formats() {
+    let sd = SData::new2(2, 200);
+    let state = State::random(50);
+    let mut sd2 = SData::new2(2, 200);
+    sd2.weights = Some(state.answers().iter().enumerate().map(|(i, aw)| (*aw, i as u32 % 3 + 1)).collect());
+
+    // both formats round trip, with weights
+    for sd in [sd, sd2] {
+      let dt = state.solve(&sd, u32::MAX).unwrap();
+      let text = dt.to_text();
+      let js = dt.to_json().to_string();
+      assert_eq!(DTree::from_text(&text), Some(dt.clone()));
+      assert_eq!(serde_json::from_str(&js).ok().and_then(|js| DTree::from_json(&js)), Some(dt.clone()));
+      let mut aws1 = dt.answers();
+      let mut aws2 = state.answers();
+      aws1.sort();
+      aws2.sort();
+      assert_eq!(aws1, aws2);
+
+      // other versions and wrong totals aren't read
+      let text2 = text.replacen("hustle-dtree 1", "hustle-dtree 2", 1);
+      assert_eq!(DTree::from_text(&text2), None);
+      let (word, tot) = match &dt {
+        DTree::Node { word, tot, .. } => (*word, *tot),
+        DTree::Leaf => unreachable!(),
+      };
+      let text3 = text.replacen(&format!("{} {}", word, tot), &format!("{} {}", word, tot + 1), 1);
+      assert_eq!(DTree::from_text(&text3), None);
+    }
+  }
+
+  #[test]
+  fn selby_format() {
+    let sd = SData::new2(2, 200);
+    let state = State::random(50);
+    let dt = state.solve(&sd, u32::MAX).unwrap();
+    let selby = dt.to_selby();
+    assert_eq!(selby.lines().count(), state.answers().len());
+    assert_eq!(DTree::detect(&selby), TreeFormat::Selby);
+    assert_eq!(DTree::from_selby(&selby), Some(dt));
+
+    // spaces take the columns above
+    let full = "salet BBBBB1 courd BBBBB2 nymph BYBBB3 jiffy GGGGG4\n\
+                salet BBBBB1 courd BBBBB2 nymph GGGGG3\n\
+                salet BBBBB1 courd GGGGG2\n";
+    let short = format!(
+      "salet BBBBB1 courd BBBBB2 nymph BYBBB3 jiffy GGGGG4\n{}nymph GGGGG3\n{}courd GGGGG2\n",
+      " ".repeat(26),
+      " ".repeat(13),
+    );
+    let dt = DTree::from_selby(full).unwrap();
+    assert_eq!(DTree::from_selby(&short), Some(dt.clone()));
+    assert_eq!(dt.to_selby(), full);
+    assert_eq!(dt.get_tot(), 4 + 3 + 2);
+
+    // answers found twice, different guesses at a node, or wrong turns aren't read
+    for bad in [
+      "salet BBBBB1 courd BBBBB2 jiffy GGGGG3\nsalet BBBBB1 courd BBBBB2 nymph GGGGG3\n",
+      "salet BBBBB1 courd GGGGG2\nsalet BBBBB1 courd GGGGG2\n",
+      "salet BBBBB1 courd GGGGG2\ntrace BBBBB1 courd GGGGG2\n",
+      "salet BBBBB1 courd GGGGG3\n",
+      "salet GGGGG1 courd GGGGG2\n",
+      "salet BBBBB1 courd\n",
+    ] {
+      assert_eq!(DTree::from_selby(bad), None, "{}", bad);
+    }
+  }
+
+  #[test]
+  fn verify_tree() {
+    for hard in [false, true] {
+      let sd = SData::new2(2, 200);
+      let state = State { hard, ..State::random(50) };
+      let dt = state.solve(&sd, u32::MAX).unwrap();
+      let aws = state.answers();
+      assert_eq!(dt.verify(&aws, state.n, hard), Vec::<String>::new());
+      if let DTree::Node { word, .. } = &dt {
+        assert!(dt.words().contains(word));
+      }
+
+      // too many turns
+      let depth = dt.depth() as u32;
+      if depth > 1 {
+        assert!(!dt.verify(&aws, depth - 1, hard).is_empty());
+      }
+
+      // an answer left out, or one the tree doesn't know
+      if aws.len() > 1 {
+        assert!(!dt.verify(&aws[1..], state.n, hard).is_empty());
+      }
+      let extra = state.fbt.aws.iter().find(|aw| !aws.contains(aw)).unwrap();
+      let mut aws2 = aws.clone();
+      aws2.push(*extra);
+      assert!(!dt.verify(&aws2, state.n, hard).is_empty());
+    }
+
+    // feedback no answer gives, and a guess breaking a hint
+    let w = |s| Word::from_str(s).unwrap();
+    let dt = DTree::from_selby("salet BBBBB1 courd GGGGG2\n").unwrap();
+    assert_eq!(dt.verify(&[w("courd")], 6, true), Vec::<String>::new());
+    let dt = DTree::from_selby("salet BBBBY1 courd GGGGG2\n").unwrap();
+    assert_eq!(dt.verify(&[w("courd")], 6, false).len(), 2);
+    let dt = DTree::from_selby("salet BBBBB1 stale BBBBB2 courd GGGGG3\n").unwrap();
+    assert_eq!(dt.verify(&[w("courd")], 6, false), Vec::<String>::new());
+    assert_eq!(dt.verify(&[w("courd")], 6, true).len(), 1);
+  }
+
+  #[test]
+  fn trace_tree() {
+    let sd = SData::new2(2, 200);
+    let state = State::random(50);
+    let dt = state.solve(&sd, u32::MAX).unwrap();
+
+    // each answer's line follows the tree to it, adding up to the total
+    let mut tot = 0;
+    for aw in state.answers() {
+      let line = dt.trace(aw).unwrap();
+      let mut s = state.clone();
+      for (gw, fb) in &line {
+        assert_eq!(Feedback::from(*gw, aw), Some(*fb));
+        s = s.fb_follow(*gw, *fb);
+      }
+      assert_eq!(line.last().unwrap(), &(aw, Feedback::correct(aw.wlen)));
+      assert_eq!(s.answers(), vec![aw]);
+      tot += line.len() as u32;
+    }
+    assert_eq!(tot, dt.get_tot());
+
+    // answers it doesn't find
+    let extra = state.fbt.aws.iter().find(|aw| !state.is_answer(aw)).unwrap();
+    assert_eq!(dt.trace(*extra), None);
+    assert_eq!(DTree::Leaf.trace(*extra), None);
+  }
+
+  #[test]
+  fn cancel_solve() {
+    let sd1 = SData::new2(2, 200);
+    let state = State::random(50);
+    let dt = state.solve(&sd1, u32::MAX).unwrap();
+
+    // with no time limit, widening ends with the same ntops
+    let sd2 = SData::new2(2, 200);
+    let dt2 = state.solve_anytime(None, &sd2).unwrap();
+    assert!(dt2.get_tot() <= dt.get_tot());
+
+    // nothing is found once cancelled, except by the greedy pass
+    let mut sd3 = SData::new2(2, 200);
+    sd3.deadline = Some(Instant::now());
+    assert!(sd3.cancelled());
+    assert!(state.solve(&sd3, u32::MAX).is_none());
+    let sd4 = SData::new2(2, 1);
+    let dt3 = state.solve_anytime(None, &sd3).unwrap();
+    assert_eq!(Some(dt3.get_tot()), state.solve(&sd4, u32::MAX).map(|dt| dt.get_tot()));
+  }
+
+  #[test]
+  fn stats_solve() {
+    // big enough not to be an endgame, and the same every run
+    let sd = SData::new2(2, 200);
+    let state = State::from_table(DEFFBT.clone(), &DEFFBT.aws[..100], NGUESSES as u32, false);
+    state.solve(&sd, u32::MAX).unwrap();
+    let get = |x: &AtomicU64| x.load(Ordering::Relaxed);
+    // every cache miss is expanded
+    assert!(get(&sd.stats.nodes) > 0);
+    assert_eq!(get(&sd.stats.nodes), get(&sd.stats.misses));
+
+    // solving again is a single cache hit
+    let hits = get(&sd.stats.hits);
+    state.solve(&sd, u32::MAX).unwrap();
+    assert_eq!(get(&sd.stats.hits), hits + 1);
+  }
+
+  // 16 answers, one of which gives each of the others a different feedback,
+  // so guessing it first takes 1 + 2 * 15 = 31, the fewest possible
+  fn split_state() -> State {
+    let aw = Word::from_str("slate").unwrap();
+    let mut aws = vec![aw];
+    let mut fbs = Vec::new();
+    for w in DEFFBT.aws.iter() {
+      let fb = Feedback::from(aw, *w).unwrap();
+      if aws.len() < 16 && !fb.is_correct() && !fbs.contains(&fb) {
+        fbs.push(fb);
+        aws.push(*w);
+      }
+    }
+    State::from_table(DEFFBT.clone(), &aws, NGUESSES as u32, false)
+  }
+
+  #[test]
+  fn lbprune_unproven() {
+    let state = split_state();
+    let sd1 = SData::new2(1000, 10);
+    let mut sd2 = SData::new2(1000, 10);
+    sd2.lbprune = true;
+
+    // the generated bound is more than this set takes, so it's only used if asked
+    assert_eq!(sd1.lbound(&state), 31);
+    assert!(sd2.lbound(&state) > 31);
+    assert_eq!(state.solve(&sd1, u32::MAX).map(|dt| dt.get_tot()), Some(31));
+  }
+
+  #[test]
+  fn long_solve() {
+    // every length works the same way, including the longest in the banks
+    for wlen in [8, 15] {
+      let (gwb, awb) = WBank::from2("/usr/share/hustle/bank2.csv", wlen).unwrap();
+      let fbt = Arc::new(FbTable::from_banks(&gwb, &awb));
+      let state = State::random_from(fbt, 20);
+      let dt = state.solve(&SData::new2(2, 200), u32::MAX).unwrap();
+      assert_eq!(dt.get_hist().iter().sum::<u32>(), state.aws.len() as u32);
+      for (_, s2) in state.fb_partition(&state.answers()[0]) {
+        assert!(s2.aws.len() < state.aws.len() || state.aws.len() == 1);
+      }
+    }
+  }
 }
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use serde_json::{json, Value};
+
+/// search statistics, shared between threads
+#[derive(Debug)]
+pub struct Stats {
+  /// states whose top words were tried
+  pub nodes: AtomicU64,
+  /// cache reads that found a tree
+  pub hits: AtomicU64,
+  /// cache reads that didn't
+  pub misses: AtomicU64,
+  /// guesses or states cut by beta
+  pub cutoffs: AtomicU64,
+  /// states solved by an answer splitting the rest
+  pub endgames: AtomicU64,
+  /// when solving started
+  pub start: Instant,
+}
+
+impl Stats {
+  pub fn new() -> Self {
+    Self {
+      nodes: AtomicU64::new(0),
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+      cutoffs: AtomicU64::new(0),
+      endgames: AtomicU64::new(0),
+      start: Instant::now(),
+    }
+  }
+
+  #[inline]
+  pub fn inc(x: &AtomicU64) {
+    x.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub fn elapsed(&self) -> f64 {
+    self.start.elapsed().as_millis() as f64 / 1000.
+  }
+
+  pub fn to_json(&self) -> Value {
+    let get = |x: &AtomicU64| x.load(Ordering::Relaxed);
+    json!({
+      "nodes": get(&self.nodes),
+      "hits": get(&self.hits),
+      "misses": get(&self.misses),
+      "cutoffs": get(&self.cutoffs),
+      "endgames": get(&self.endgames),
+    })
+  }
+}
+
+impl fmt::Display for Stats {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let get = |x: &AtomicU64| x.load(Ordering::Relaxed);
+    let hits = get(&self.hits);
+    let misses = get(&self.misses);
+    writeln!(f, "nodes expanded: {}", get(&self.nodes))?;
+    writeln!(
+      f,
+      "cache hits: {}, misses: {} ({:.1}% hit rate)",
+      hits,
+      misses,
+      100. * hits as f64 / (hits + misses).max(1) as f64
+    )?;
+    writeln!(f, "cutoffs: {}", get(&self.cutoffs))?;
+    write!(f, "endgames: {}", get(&self.endgames))
+  }
+}
@@ -0,0 +1,146 @@
+/// set of word indices (into a FbTable's guesses or answers)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct WSet {
+  bits: Vec<u64>,
+  len: usize,
+}
+
+/// iterator over a WSet's indices in order
+pub struct Iter<'a> {
+  bits: &'a [u64],
+  i: usize,
+  cur: u64,
+  left: usize,
+}
+
+impl WSet {
+  /// empty set of indices below cap
+  pub fn new(cap: usize) -> Self {
+    Self {
+      bits: vec![0; cap.div_ceil(64)],
+      len: 0,
+    }
+  }
+
+  /// set of all indices below cap
+  pub fn full(cap: usize) -> Self {
+    let mut bits = vec![u64::MAX; cap / 64];
+    if !cap.is_multiple_of(64) {
+      bits.push((1 << (cap % 64)) - 1);
+    }
+    Self { bits, len: cap }
+  }
+
+  pub fn from_indices<I>(cap: usize, idxs: I) -> Self
+  where I: IntoIterator<Item = u32>, {
+    let mut set = Self::new(cap);
+    for i in idxs {
+      set.insert(i);
+    }
+    set
+  }
+
+  pub fn insert(&mut self, i: u32) {
+    let (j, b) = (i as usize / 64, 1 << (i % 64));
+    if self.bits[j] & b == 0 {
+      self.bits[j] |= b;
+      self.len += 1;
+    }
+  }
+
+  pub fn contains(&self, i: u32) -> bool {
+    self.bits.get(i as usize / 64).is_some_and(|x| x & 1 << (i % 64) != 0)
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn first(&self) -> Option<u32> {
+    self.iter().next()
+  }
+
+  pub fn iter(&self) -> Iter<'_> {
+    Iter {
+      bits: &self.bits,
+      i: 0,
+      cur: self.bits.first().cloned().unwrap_or(0),
+      left: self.len,
+    }
+  }
+
+  /// indices in either set (of the same cap)
+  pub fn union(&self, other: &Self) -> Self {
+    Self::from_bits(self.bits.iter().zip(&other.bits).map(|(a, b)| a | b).collect())
+  }
+
+  fn from_bits(bits: Vec<u64>) -> Self {
+    let len = bits.iter().map(|x| x.count_ones() as usize).sum();
+    Self { bits, len }
+  }
+
+  /// indices whose ids (in iteration order) satisfy f
+  pub fn filter<F>(&self, ids: &[u32], f: F) -> Self
+  where F: Fn(u32) -> bool, {
+    let idxs = self.iter()
+      .zip(ids)
+      .filter(|(_, id)| f(**id))
+      .map(|(i, _)| i);
+    Self::from_indices(self.bits.len() * 64, idxs)
+  }
+}
+
+impl<'a> Iterator for Iter<'a> {
+  type Item = u32;
+
+  fn next(&mut self) -> Option<u32> {
+    while self.cur == 0 {
+      self.i += 1;
+      self.cur = *self.bits.get(self.i)?;
+    }
+    let tz = self.cur.trailing_zeros();
+    self.cur &= self.cur - 1;
+    self.left -= 1;
+    Some(self.i as u32 * 64 + tz)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (self.left, Some(self.left))
+  }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn set_ops() {
+    let idxs = vec![0, 5, 63, 64, 100, 129];
+    let set = WSet::from_indices(130, idxs.clone());
+    assert_eq!(set.len(), 6);
+    assert_eq!(set.iter().collect::<Vec<u32>>(), idxs);
+    assert!(set.contains(63) && !set.contains(62) && !set.contains(1000));
+    assert_eq!(set.first(), Some(0));
+
+    let full = WSet::full(130);
+    assert_eq!(full.len(), 130);
+    assert_eq!(full.iter().count(), 130);
+    assert_eq!(WSet::from_indices(130, 0..130), full);
+
+    // keep the odd ids
+    let ids: Vec<u32> = (0..6).collect();
+    let odd = set.filter(&ids, |id| id % 2 == 1);
+    assert_eq!(odd.iter().collect::<Vec<u32>>(), vec![5, 64, 129]);
+    assert!(WSet::new(130).is_empty());
+
+    let evens = WSet::from_indices(130, (0..130).step_by(2));
+    assert_eq!(set.union(&evens).len(), 68);
+    assert_eq!(set.union(&full), full);
+  }
+}